@@ -0,0 +1,280 @@
+//! Measures how fast the solver counts the solutions of puzzle corpora across engines, thread counts, and heuristics.
+//! ```text
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Each configuration counts every puzzle of a corpus once per run on its own thread pool.
+//!  Warm-up runs are timed but left out of the results, so caches and the pool are settled first.
+//!  The candidates engine always branches like the mrv heuristic, so it is measured once per thread count.
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Median      the middle time of the measured runs
+//!  p95         the time 95% of the measured runs finished within
+//!  Puzzles/s   the puzzles counted per second over every measured run
+//!  Speedup     the median with the fewest threads divided by this median, for the same corpus, engine, and heuristic
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::SudokuBoard;
+use crate::sudoku::candidates::CandidateBoard;
+use crate::sudoku::heuristic;
+use rayon::ThreadPoolBuilder;
+use serde_json::{json, Value};
+use std::io;
+use std::time::Instant;
+
+/// A named set of puzzles that is timed as a whole.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Corpus {
+    pub name: String,
+    pub puzzles: Vec<String>,
+}
+
+/// What to measure and how often.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The names of the engines to measure, each one of [ENGINES](../sudoku/candidates/constant.ENGINES.html).
+    pub engines: Vec<String>,
+    /// The number of threads of each pool to measure with.
+    pub threads: Vec<usize>,
+    /// The names of the heuristics to measure, each one of [HEURISTICS](../sudoku/heuristic/constant.HEURISTICS.html).
+    pub heuristics: Vec<String>,
+    /// The seed for randomized heuristics, so every run searches the same way.
+    pub seed: u64,
+    /// The number of runs before measuring.
+    pub warmup: usize,
+    /// The number of measured runs.
+    pub repetitions: usize,
+}
+
+/// The times of every measured run of one configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub corpus: String,
+    pub engine: String,
+    pub heuristic: String,
+    pub threads: usize,
+    /// The number of puzzles counted in each run.
+    pub puzzles: usize,
+    /// The number of solutions found in each run.
+    pub solutions: usize,
+    /// The seconds each measured run took, in the order they ran.
+    pub seconds: Vec<f64>,
+}
+
+impl Measurement {
+    /// Returns the middle time of the measured runs.
+    pub fn median(&self) -> f64 {
+        percentile(&self.seconds, 50.0)
+    }
+
+    /// Returns the time that 95% of the measured runs finished within.
+    pub fn p95(&self) -> f64 {
+        percentile(&self.seconds, 95.0)
+    }
+
+    /// Returns the number of puzzles counted per second over every measured run.
+    pub fn throughput(&self) -> f64 {
+        let total: f64 = self.seconds.iter().sum();
+        (self.puzzles * self.seconds.len()) as f64 / total.max(f64::EPSILON)
+    }
+}
+
+/// Returns the engine and heuristic names of every configuration to measure, in the order they are measured.
+fn searches(config: &Config) -> Vec<(String, String)> {
+    let mut searches = Vec::new();
+    for engine in &config.engines {
+        if "candidates" == engine {
+            searches.push((engine.clone(), String::from("mrv")));
+        } else {
+            for name in &config.heuristics {
+                searches.push((engine.clone(), name.clone()));
+            }
+        }
+    }
+    searches
+}
+
+/// Measures every corpus with every engine, heuristic, and thread count, reporting each measurement as it finishes.
+pub fn run<F>(corpora: &[Corpus], config: &Config, mut report: F) -> io::Result<Vec<Measurement>>
+where
+    F: FnMut(&Measurement),
+{
+    let mut measurements = Vec::new();
+    for corpus in corpora {
+        for (engine, name) in searches(config) {
+            for &threads in &config.threads {
+                let pool = ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(io::Error::other)?;
+                let heuristic = heuristic::by_name(&name, config.seed).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("unknown heuristic: {}", name),
+                    )
+                })?;
+                let count_all = || {
+                    corpus
+                        .puzzles
+                        .iter()
+                        .map(|puzzle| match engine.as_str() {
+                            "candidates" => CandidateBoard::from(puzzle.as_str()).count_solutions(),
+                            _ => SudokuBoard::from(puzzle.as_str())
+                                .with_heuristic(heuristic.clone())
+                                .count_solutions(),
+                        })
+                        .sum::<usize>()
+                };
+                let mut solutions = 0;
+                for _ in 0..config.warmup {
+                    solutions = pool.install(count_all);
+                }
+                let seconds = (0..config.repetitions.max(1))
+                    .map(|_| {
+                        let now = Instant::now();
+                        solutions = pool.install(count_all);
+                        now.elapsed().as_secs_f64()
+                    })
+                    .collect();
+                let measurement = Measurement {
+                    corpus: corpus.name.clone(),
+                    engine: engine.clone(),
+                    heuristic: name.clone(),
+                    threads,
+                    puzzles: corpus.puzzles.len(),
+                    solutions,
+                    seconds,
+                };
+                report(&measurement);
+                measurements.push(measurement);
+            }
+        }
+    }
+    Ok(measurements)
+}
+
+/// Returns the median with the fewest threads for the same corpus, engine, and heuristic divided by this median.
+pub fn speedup(measurements: &[Measurement], measurement: &Measurement) -> f64 {
+    let baseline = measurements
+        .iter()
+        .filter(|other| {
+            other.corpus == measurement.corpus
+                && other.engine == measurement.engine
+                && other.heuristic == measurement.heuristic
+        })
+        .min_by_key(|other| other.threads)
+        .unwrap_or(measurement);
+    baseline.median() / measurement.median().max(f64::EPSILON)
+}
+
+/// Formats the measurements as a table with one row per configuration.
+pub fn table(measurements: &[Measurement]) -> String {
+    let width = measurements
+        .iter()
+        .map(|measurement| measurement.corpus.len())
+        .chain(Some("Corpus".len()))
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "  {:<width$}  {:<10}  {:<10}  {:>7}  {:>9}  {:>12}  {:>12}  {:>12}  {:>7}\n",
+        "Corpus",
+        "Engine",
+        "Heuristic",
+        "Threads",
+        "Solutions",
+        "Median (s)",
+        "p95 (s)",
+        "Puzzles/s",
+        "Speedup",
+        width = width
+    );
+    for measurement in measurements {
+        table += &format!(
+            "  {:<width$}  {:<10}  {:<10}  {:>7}  {:>9}  {:>12.6}  {:>12.6}  {:>12.2}  {:>6.2}x\n",
+            measurement.corpus,
+            measurement.engine,
+            measurement.heuristic,
+            measurement.threads,
+            measurement.solutions,
+            measurement.median(),
+            measurement.p95(),
+            measurement.throughput(),
+            speedup(measurements, measurement),
+            width = width
+        );
+    }
+    table
+}
+
+/// Formats the measurements as a JSON array with the same columns as the table and the time of every run.
+pub fn json(measurements: &[Measurement]) -> Value {
+    measurements
+        .iter()
+        .map(|measurement| {
+            json!({
+                "corpus": measurement.corpus,
+                "engine": measurement.engine,
+                "heuristic": measurement.heuristic,
+                "threads": measurement.threads,
+                "puzzles": measurement.puzzles,
+                "solutions": measurement.solutions,
+                "median_seconds": measurement.median(),
+                "p95_seconds": measurement.p95(),
+                "puzzles_per_second": measurement.throughput(),
+                "speedup": speedup(measurements, measurement),
+                "seconds": measurement.seconds,
+            })
+        })
+        .collect()
+}
+
+/// Returns the nearest-rank percentile of some times.
+fn percentile(seconds: &[f64], percent: f64) -> f64 {
+    let mut sorted = seconds.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted
+        .get(rank.clamp(1, sorted.len().max(1)) - 1)
+        .copied()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_every_configuration() {
+        let corpora = vec![Corpus {
+            name: String::from("two"),
+            puzzles: vec![
+                String::from(".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31."),
+                String::from(".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31."),
+            ],
+        }];
+        let config = Config {
+            engines: vec![String::from("masks"), String::from("candidates")],
+            threads: vec![1, 2],
+            heuristics: vec![String::from("mrv"), String::from("random")],
+            seed: 0,
+            warmup: 1,
+            repetitions: 3,
+        };
+        let mut reported = 0;
+        let measurements = run(&corpora, &config, |_| reported += 1).unwrap();
+        assert_eq!(6, reported);
+        assert!(measurements
+            .iter()
+            .all(|measurement| 1 + 35 == measurement.solutions && 3 == measurement.seconds.len()));
+        assert!((speedup(&measurements, &measurements[0]) - 1.0).abs() < f64::EPSILON);
+        assert_eq!(7, table(&measurements).lines().count());
+        assert_eq!(json!(2), json(&measurements)[1]["threads"]);
+        assert_eq!(json!("candidates"), json(&measurements)[5]["engine"]);
+    }
+
+    #[test]
+    fn percentiles() {
+        let seconds: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(10.0, percentile(&seconds, 50.0));
+        assert_eq!(19.0, percentile(&seconds, 95.0));
+        assert_eq!(3.0, percentile(&[3.0], 95.0));
+    }
+}
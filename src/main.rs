@@ -1,14 +1,53 @@
+mod bench;
+mod pipe;
+mod server;
 pub mod sudoku;
 use crate::SudokuError::{IOError, RayonError};
 use ansi_escapes::ClearScreen;
+use pipe::PIPE_MODES;
 use rayon::ThreadPoolBuilder;
+use std::collections::HashSet;
+use std::fmt;
 use std::fs;
 use std::fs::File;
-use std::io::Write;
+use std::io;
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use structopt::StructOpt;
-use sudoku::board::SudokuBoard;
+use sudoku::analyze;
+use sudoku::board::{SearchEvent, SudokuBoard, Uniqueness};
+use sudoku::booklet;
+use sudoku::booklet::PAPERS;
+use sudoku::candidates::{CandidateBoard, ENGINES};
+use sudoku::canon;
+use sudoku::checkpoint;
+use sudoku::checkpoint::Checkpoint;
+use sudoku::estimate;
+use sudoku::explain;
+use sudoku::fix;
+use sudoku::fix::SYMMETRIES;
+use sudoku::formats;
+use sudoku::formats::{Grid, FORMATS};
+use sudoku::heuristic;
+use sudoku::heuristic::HEURISTICS;
+use sudoku::memo;
+use sudoku::minimize;
+use sudoku::minimize::CLUE_ORDERS;
+use sudoku::random::SplitMix64;
+use sudoku::recognize;
+use sudoku::render;
+use sudoku::render::{Style, RENDER_FORMATS};
+use sudoku::sample;
+use sudoku::shard;
+use sudoku::shard::{Shard, MERGE_MODES};
+use sudoku::trace::{read_trace, read_trace_from, Trace};
+use sudoku::transform;
+use sudoku::transform::{Reflection, REFLECTIONS};
+use sudoku::tree::SearchTree;
+use sudoku::verify;
 
 #[derive(Debug)]
 enum SudokuError {
@@ -16,6 +55,17 @@ enum SudokuError {
     RayonError(rayon::ThreadPoolBuildError),
 }
 
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IOError(e) => write!(f, "{}", e),
+            RayonError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SudokuError {}
+
 impl From<std::io::Error> for SudokuError {
     fn from(e: std::io::Error) -> Self {
         IOError(e)
@@ -41,11 +91,51 @@ enum Action {
     WatchAll(Watch),
     /// Counts the number of solutions to a sudoku puzzle.
     CountAll(Count),
+    /// Watch a search recorded with --trace play back in the terminal.
+    Replay(Replay),
+    /// Checks whether a puzzle has exactly one solution, stopping as soon as a second is found.
+    CheckUnique(Check),
+    /// Removes clues from a unique puzzle until every remaining clue is necessary.
+    Minimize(Minimize),
+    /// Prints the canonical form of a puzzle, which is the same for every equivalent puzzle.
+    Canon(Canon),
+    /// Checks whether two puzzles are equivalent under relabeling, row/column swaps, and transposition.
+    Same(Same),
+    /// Rotates, reflects, relabels, or reorders a puzzle and its solution into an equivalent puzzle.
+    Transform(Transform),
+    /// Answers solve, count, validate, generate, and grade requests over HTTP with JSON.
+    Serve(Serve),
+    /// Reads one puzzle per line from stdin and writes one answer per line to stdout.
+    Pipe(Pipe),
+    /// Converts puzzles between the file formats of other sudoku tools.
+    Convert(Convert),
+    /// Draws a puzzle as an SVG or PNG image.
+    Render(Render),
+    /// Lays out puzzles and their answers as a printable PDF booklet.
+    Booklet(Booklet),
+    /// Reads a puzzle from a PNG or JPEG image of a grid.
+    ImportImage(ImportImage),
+    /// Times counting the solutions of puzzle files across engines, thread counts, and heuristics.
+    Bench(Bench),
+    /// Checks a proposed solution against a puzzle and shows every square that breaks a rule.
+    Verify(Verify),
+    /// Combines the outputs of count-all or find-all runs with --shard into one result.
+    Merge(Merge),
+    /// Estimates the number of solutions from random walks down the search tree.
+    Estimate(Estimate),
+    /// Samples solutions at random, each about as likely as any other.
+    Sample(Sample),
+    /// Shows which empty squares have the same value in every solution, and the values of the others.
+    Analyze(Analyze),
+    /// Finds the fewest clues to add to a puzzle with several solutions so that it has exactly one.
+    FixUnique(FixUnique),
+    /// Finds a minimal set of givens that already has no solution, for puzzles without one.
+    ExplainUnsolvable(ExplainUnsolvable),
 }
 
 #[derive(StructOpt, Debug)]
 struct Output {
-    /// The path to a puzzle or a puzzle string (for examples: sudoku-cli help)
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
     #[structopt(short = "i", long = "input")]
     input: String,
 
@@ -60,22 +150,521 @@ struct Output {
     /// Writes solutions as a compact string of 81 consecutive digits.
     #[structopt(short = "c", long = "compact")]
     compact: bool,
+
+    /// Finds one solution drawn at random, each about as likely as any other, the same way as sample
+    #[structopt(
+        short = "r",
+        long = "random",
+        conflicts_with_all = &["heuristic", "trace", "tree", "engine"]
+    )]
+    random: bool,
+
+    /// The heuristic that chooses the next square to fill and the order of its values
+    #[structopt(long = "heuristic", default_value = "mrv", possible_values = &HEURISTICS)]
+    heuristic: String,
+
+    /// The seed for randomized heuristics [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// The path to a file to which every step of a sequential search will be recorded.
+    #[structopt(long = "trace")]
+    trace: Option<String>,
+
+    /// The path to a file to which the search tree will be written as a Graphviz DOT graph.
+    #[structopt(long = "tree")]
+    tree: Option<String>,
+
+    /// The maximum depth of the search tree written by --tree
+    #[structopt(long = "tree-depth", default_value = "8")]
+    tree_depth: usize,
+
+    /// The search engine: masks recomputes options from row/col/box masks, candidates keeps them per square
+    #[structopt(long = "engine", default_value = "masks", possible_values = &ENGINES)]
+    engine: String,
+
+    /// Searches only slice i of N disjoint slices of the search, written as i/N and numbered from 0
+    #[structopt(long = "shard", conflicts_with_all = &["trace", "tree"])]
+    shard: Option<Shard>,
 }
 
 #[derive(StructOpt, Debug)]
 struct Watch {
-    /// The path to a puzzle or a puzzle string (for examples: sudoku-cli help)
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
     #[structopt(short = "i", long = "input")]
     input: String,
 
     /// The number of milliseconds per frame
     #[structopt(short = "m", long = "ms-per-frame", default_value = "50")]
     ms_per_frame: u64,
+
+    /// The heuristic that chooses the next square to fill and the order of its values
+    #[structopt(long = "heuristic", default_value = "mrv", possible_values = &HEURISTICS)]
+    heuristic: String,
+
+    /// The seed for randomized heuristics [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
 }
 
 #[derive(StructOpt, Debug)]
 struct Count {
-    /// The path to a puzzle or a puzzle string (for examples: sudoku-cli help)
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// The heuristic that chooses the next square to fill and the order of its values
+    #[structopt(long = "heuristic", default_value = "mrv", possible_values = &HEURISTICS)]
+    heuristic: String,
+
+    /// The seed for randomized heuristics [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// The path to a file to which every step of a sequential search will be recorded.
+    #[structopt(long = "trace")]
+    trace: Option<String>,
+
+    /// The path to a file to which the search tree will be written as a Graphviz DOT graph.
+    #[structopt(long = "tree")]
+    tree: Option<String>,
+
+    /// The maximum depth of the search tree written by --tree
+    #[structopt(long = "tree-depth", default_value = "8")]
+    tree_depth: usize,
+
+    /// The search engine: masks recomputes options from row/col/box masks, candidates keeps them per square
+    #[structopt(long = "engine", default_value = "masks", possible_values = &ENGINES)]
+    engine: String,
+
+    /// Searches only slice i of N disjoint slices of the search, written as i/N and numbered from 0
+    #[structopt(long = "shard", conflicts_with_all = &["trace", "tree"])]
+    shard: Option<Shard>,
+
+    /// The path to a file in which every completed branch of the search and its count will be recorded
+    #[structopt(long = "checkpoint", conflicts_with_all = &["trace", "tree"])]
+    checkpoint: Option<String>,
+
+    /// Continues from the branches recorded in --checkpoint instead of starting over.
+    #[structopt(long = "resume", requires = "checkpoint")]
+    resume: bool,
+
+    /// Counts without visiting each solution, remembering the count of every state reached at the start of a row
+    #[structopt(
+        long = "exact",
+        conflicts_with_all = &["trace", "tree", "shard", "checkpoint", "engine", "heuristic", "seed", "threads"]
+    )]
+    exact: bool,
+}
+
+#[derive(StructOpt, Debug)]
+struct Check {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// Writes solutions as a compact string of 81 consecutive digits.
+    #[structopt(short = "c", long = "compact")]
+    compact: bool,
+}
+
+#[derive(StructOpt, Debug)]
+struct Minimize {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// The order in which clues are tried for removal
+    #[structopt(long = "order", default_value = "row-major", possible_values = &CLUE_ORDERS)]
+    order: String,
+
+    /// The seed for the random order [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Canon {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// Treats each non-empty line of the input as a separate puzzle and prints one canonical form per line.
+    #[structopt(short = "l", long = "lines")]
+    lines: bool,
+
+    /// With --lines, prints each canonical form only the first time it appears.
+    #[structopt(short = "u", long = "unique", requires = "lines")]
+    unique: bool,
+}
+
+#[derive(StructOpt, Debug)]
+struct Same {
+    /// The path to the first puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "a", long = "first")]
+    first: String,
+
+    /// The path to the second puzzle, a puzzle string, or - to read from stdin
+    #[structopt(short = "b", long = "second")]
+    second: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Transform {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The path to the puzzle's solution or a solution string, which is transformed along with the puzzle
+    #[structopt(short = "s", long = "solution")]
+    solution: Option<String>,
+
+    /// The path to a file to which the transformed puzzles will be written.
+    #[structopt(short = "o", long = "output")]
+    output: Option<String>,
+
+    /// Writes puzzles as a compact string of 81 characters, followed by the solution if there is one.
+    #[structopt(short = "c", long = "compact")]
+    compact: bool,
+
+    /// Reorders the rows, for example 102345678 swaps the first two rows; rows must stay in their bands
+    #[structopt(long = "rows", parse(try_from_str = transform::parse_rows))]
+    rows: Option<[usize; 9]>,
+
+    /// Reorders the bands of three rows, for example 201 moves the bottom band to the top
+    #[structopt(long = "bands", parse(try_from_str = transform::parse_bands))]
+    bands: Option<[usize; 3]>,
+
+    /// Swaps the rows and columns
+    #[structopt(long = "transpose")]
+    transpose: bool,
+
+    /// Mirrors the puzzle across a line
+    #[structopt(long = "reflect", possible_values = &REFLECTIONS)]
+    reflect: Option<String>,
+
+    /// Rotates the puzzle clockwise by a number of quarter turns
+    #[structopt(long = "rotate")]
+    rotate: Option<usize>,
+
+    /// Relabels the digits, for example 912345678 replaces each 1 with a 9
+    #[structopt(long = "relabel", parse(try_from_str = transform::parse_digits))]
+    relabel: Option<[u8; 9]>,
+
+    /// Finishes with a random combination of transposing, swaps, and relabeling
+    #[structopt(short = "r", long = "random")]
+    random: bool,
+
+    /// The seed for --random [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// The number of variants to write, each with a different random combination [default: 1]
+    #[structopt(short = "n", long = "count", requires = "random")]
+    count: Option<usize>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Serve {
+    /// The port to listen on
+    #[structopt(short = "p", long = "port", default_value = "8080")]
+    port: u16,
+
+    /// The address to listen on
+    #[structopt(long = "host", default_value = "127.0.0.1")]
+    host: String,
+
+    /// The number of threads shared by every request [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// The maximum number of requests solved at once; more are answered with 503 [default: CPU count]
+    #[structopt(long = "max-jobs")]
+    max_jobs: Option<usize>,
+
+    /// The number of milliseconds a request may take before it is answered with 504
+    #[structopt(long = "timeout-ms", default_value = "10000")]
+    timeout_ms: u64,
+}
+
+#[derive(StructOpt, Debug)]
+struct Pipe {
+    /// The answer to write for each puzzle
+    #[structopt(long = "mode", default_value = "solve", possible_values = &PIPE_MODES)]
+    mode: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Convert {
+    /// The path to the puzzles to convert, a puzzle string, or - to read from stdin
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The path to a file to which the converted puzzles will be written [default: stdout]
+    #[structopt(short = "o", long = "output")]
+    output: Option<String>,
+
+    /// The format of the input [default: from the input's extension, or txt]
+    #[structopt(long = "from", possible_values = &FORMATS)]
+    from: Option<String>,
+
+    /// The format of the output [default: from the output's extension, or txt]
+    #[structopt(long = "to", possible_values = &FORMATS)]
+    to: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Render {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The path to a file to which the image will be written [default: stdout]
+    #[structopt(short = "o", long = "output")]
+    output: Option<String>,
+
+    /// The image format [default: from the output's extension, or svg]
+    #[structopt(short = "f", long = "format", possible_values = &RENDER_FORMATS)]
+    format: Option<String>,
+
+    /// The format of the input, which keeps filled-in values for sdk and hodoku [default: from the input's extension]
+    #[structopt(long = "from", possible_values = &FORMATS)]
+    from: Option<String>,
+
+    /// Fills every empty square with the solution, drawn apart from the givens.
+    #[structopt(short = "s", long = "solve")]
+    solve: bool,
+
+    /// Draws the candidates of every empty square.
+    #[structopt(long = "candidates")]
+    candidates: bool,
+
+    /// The width and height of the image in pixels, from 45 to 4000
+    #[structopt(long = "size", default_value = "450", parse(try_from_str = render::parse_size))]
+    size: u32,
+
+    /// The font family of the digits in SVG images, which PNG images cannot use [default: Helvetica, Arial, sans-serif]
+    #[structopt(long = "font")]
+    font: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Booklet {
+    /// The path to the puzzles to print, a puzzle string, or - to read from stdin
+    #[structopt(
+        short = "i",
+        long = "input",
+        required_unless = "generate",
+        conflicts_with = "generate"
+    )]
+    input: Option<String>,
+
+    /// The number of puzzles to generate instead of reading them from an input
+    #[structopt(short = "g", long = "generate")]
+    generate: Option<usize>,
+
+    /// The seed for generated puzzles [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// The path to a file to which the PDF will be written
+    #[structopt(short = "o", long = "output")]
+    output: String,
+
+    /// The format of the input [default: from the input's extension, or txt]
+    #[structopt(long = "from", possible_values = &FORMATS)]
+    from: Option<String>,
+
+    /// The number of puzzles on each page, at most 15 on letter and 18 on a4
+    #[structopt(long = "per-page", default_value = "4")]
+    per_page: usize,
+
+    /// The heading at the top of every page
+    #[structopt(long = "title", default_value = "Sudoku")]
+    title: String,
+
+    /// The size of the pages
+    #[structopt(long = "paper", default_value = "letter", possible_values = &PAPERS)]
+    paper: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+}
+
+#[derive(StructOpt, Debug)]
+struct ImportImage {
+    /// The path to a PNG or JPEG image of a grid, or - to read it from stdin
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The path to a file to which the puzzle will be written as one line of 81 characters.
+    #[structopt(short = "o", long = "output")]
+    output: Option<String>,
+
+    /// Digits recognized with less confidence than this, from 0 to 1, are listed for checking
+    #[structopt(long = "min-confidence", default_value = "0.8")]
+    min_confidence: f64,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Bench {
+    /// The paths to the puzzle files to time, each with one or more puzzles [default: puzzles/easy, puzzles/hard]
+    #[structopt(short = "i", long = "input")]
+    inputs: Vec<String>,
+
+    /// The comma-separated thread counts to time [default: 1 and the CPU count]
+    #[structopt(short = "t", long = "threads", use_delimiter = true)]
+    threads: Vec<usize>,
+
+    /// The comma-separated engines to time
+    #[structopt(long = "engines", default_value = "masks", use_delimiter = true, possible_values = &ENGINES)]
+    engines: Vec<String>,
+
+    /// The comma-separated heuristics to time
+    #[structopt(long = "heuristics", default_value = "mrv", use_delimiter = true, possible_values = &HEURISTICS)]
+    heuristics: Vec<String>,
+
+    /// The seed for randomized heuristics
+    #[structopt(long = "seed", default_value = "0")]
+    seed: u64,
+
+    /// The number of untimed runs of each configuration before it is measured
+    #[structopt(short = "w", long = "warmup", default_value = "1")]
+    warmup: usize,
+
+    /// The number of measured runs of each configuration
+    #[structopt(short = "r", long = "repetitions", default_value = "5")]
+    repetitions: usize,
+
+    /// The format of the puzzle files [default: from each file's extension, or txt]
+    #[structopt(long = "from", possible_values = &FORMATS)]
+    from: Option<String>,
+
+    /// The path to a file to which the results will also be written as JSON.
+    #[structopt(long = "json")]
+    json: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Verify {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The path to the proposed solution, a solution string, or - to read from stdin if the puzzle is not
+    #[structopt(short = "s", long = "solution")]
+    solution: String,
+}
+
+#[derive(StructOpt, Debug)]
+struct Merge {
+    /// The paths to the outputs of every shard
+    #[structopt(short = "i", long = "input", required = true)]
+    inputs: Vec<String>,
+
+    /// How to combine the outputs: counts sums the count-all outputs, solutions joins the find-all files
+    #[structopt(long = "mode", default_value = "counts", possible_values = &MERGE_MODES)]
+    mode: String,
+
+    /// The path to a file to which the total count or every solution will be written
+    #[structopt(short = "o", long = "output")]
+    output: Option<String>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Estimate {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// The number of random walks to take, at least 2, or the minimum number with --target-error
+    #[structopt(short = "n", long = "samples", default_value = "10000", parse(try_from_str = estimate::parse_samples))]
+    samples: usize,
+
+    /// Keeps taking walks until the 95% interval is within this fraction of the estimate, such as 0.01
+    #[structopt(long = "target-error")]
+    target_error: Option<f64>,
+
+    /// The most walks to take with --target-error
+    #[structopt(long = "max-samples", default_value = "10000000")]
+    max_samples: usize,
+
+    /// The heuristic that chooses the next square to fill on each walk
+    #[structopt(long = "heuristic", default_value = "mrv", possible_values = &HEURISTICS)]
+    heuristic: String,
+
+    /// The seed for the random walks [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Sample {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// The number of solutions to sample
+    #[structopt(short = "n", long = "n", default_value = "1")]
+    n: usize,
+
+    /// The seed for the samples [default: current time]
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// The number of random probes behind each estimated count; more probes sample closer to uniformly
+    #[structopt(long = "probes", default_value = "1000")]
+    probes: usize,
+
+    /// The path to a file to which the solutions will be written.
+    #[structopt(short = "o", long = "output")]
+    output: Option<String>,
+
+    /// Writes each solution as one line of 81 digits.
+    #[structopt(short = "c", long = "compact")]
+    compact: bool,
+}
+
+#[derive(StructOpt, Debug)]
+struct Analyze {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
     #[structopt(short = "i", long = "input")]
     input: String,
 
@@ -84,6 +673,43 @@ struct Count {
     threads: Option<usize>,
 }
 
+#[derive(StructOpt, Debug)]
+struct FixUnique {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+
+    /// Adds each clue together with its mirror image, so the pattern of clues keeps this symmetry
+    #[structopt(long = "symmetry", default_value = "none", possible_values = &SYMMETRIES)]
+    symmetry: String,
+}
+
+#[derive(StructOpt, Debug)]
+struct ExplainUnsolvable {
+    /// The path to a puzzle, a puzzle string, or - to read from stdin (for examples: sudoku-cli help)
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of threads to use [default: CPU count]
+    #[structopt(short = "t", long = "threads")]
+    threads: Option<usize>,
+}
+
+#[derive(StructOpt, Debug)]
+struct Replay {
+    /// The path to a trace recorded with --trace, or - to read it from stdin
+    #[structopt(short = "i", long = "input")]
+    input: String,
+
+    /// The number of milliseconds per frame
+    #[structopt(short = "m", long = "ms-per-frame", default_value = "50")]
+    ms_per_frame: u64,
+}
+
 const ABOUT: &str = r#"
 ABOUT:
 
@@ -91,19 +717,23 @@ ABOUT:
     
 COMMANDS:
     
-    sudoku-cli has five sub-commands, each with their own long and short options:
+    sudoku-cli has twenty-five sub-commands, each with their own long and short options:
     
         Command: sudoku-cli find-one  
             Description: Finds one solution to a puzzle and writes it to an output.
-                Long:    --input=value,  --output=value,  --threads=value,  --compact
-                Short:        -i=value,        -o=value,         -t=value,         -c
-                Default:      Required,        Terminal,   Number of CPUs,        Off
+                Long:    --input=value,  --output=value,  --threads=value,  --compact,  --trace=value,  --tree=value,  --tree-depth=value
+                Short:        -i=value,        -o=value,         -t=value,         -c,
+                Default:      Required,        Terminal,   Number of CPUs,        Off,          None,          None,                   8
+                Long:    --engine=value
+                Default:          masks
             
         Command: sudoku-cli find-all
             Description: Finds all solutions to a puzzle and writes them to an output.
-                Long:    --input=value,  --output=value,  --threads=value,  --compact
-                Short:        -i=value,        -o=value,         -t=value,         -c
-                Default:      Required,        Terminal,   Number of CPUs,        Off
+                Long:    --input=value,  --output=value,  --threads=value,  --compact,  --trace=value,  --tree=value,  --tree-depth=value
+                Short:        -i=value,        -o=value,         -t=value,         -c,
+                Default:      Required,        Terminal,   Number of CPUs,        Off,          None,          None,                   8
+                Long:    --engine=value,  --shard=value
+                Default:          masks,           None
             
         Command: sudoku-cli watch-one 
             Description: Watch the solver find one solution in the terminal.
@@ -119,13 +749,147 @@ COMMANDS:
             
         Command: sudoku-cli count-all
             Description: Count all solutions without writing them to an output.
-                Long:    --input=value,  --threads=value
-                Short:        -i=value,         -t=value
-                Default:      Required,   Number of CPUs 
+                Long:    --input=value,  --threads=value,  --trace=value,  --tree=value,  --tree-depth=value,  --engine=value
+                Short:        -i=value,         -t=value,
+                Default:      Required,   Number of CPUs,           None,          None,                   8,           masks
+                Long:    --shard=value,  --checkpoint=value,  --resume,  --exact
+                Default:           None,                None,       Off,      Off
 
-INPUT:
+        Command: sudoku-cli check-unique
+            Description: Check whether a puzzle has exactly one solution, stopping at the second.
+                Long:    --input=value,  --threads=value,  --compact
+                Short:        -i=value,         -t=value,         -c
+                Default:      Required,   Number of CPUs,        Off
 
-    If your input is a valid file path, sudoku-cli will read from the file.
+        Command: sudoku-cli minimize
+            Description: Remove clues from a unique puzzle until every remaining clue is necessary.
+                Long:    --input=value,  --threads=value,  --order=value,  --seed=value
+                Short:        -i=value,         -t=value,
+                Default:      Required,   Number of CPUs,      row-major,  Current time
+
+        Command: sudoku-cli canon
+            Description: Print the canonical form of a puzzle, which is the same for every equivalent puzzle.
+                Long:    --input=value,  --threads=value,  --lines,  --unique
+                Short:        -i=value,         -t=value,       -l,        -u
+                Default:      Required,   Number of CPUs,      Off,       Off
+
+        Command: sudoku-cli same
+            Description: Check whether two puzzles are equivalent.
+                Long:    --first=value,  --second=value,  --threads=value
+                Short:        -a=value,        -b=value,         -t=value
+                Default:      Required,        Required,   Number of CPUs
+
+        Command: sudoku-cli transform
+            Description: Rotate, reflect, relabel, or reorder a puzzle and its solution into an equivalent puzzle.
+                Long:    --input=value,  --solution=value,  --output=value,  --compact,  --rows=value,  --bands=value,  --transpose
+                Short:        -i=value,          -s=value,        -o=value,         -c,
+                Default:      Required,              None,        Terminal,        Off,          None,           None,          Off
+                Long:    --reflect=value,  --rotate=value,  --relabel=value,  --random,  --seed=value,  --count=value
+                Short:                                                              -r,                      -n=value
+                Default:            None,             None,             None,       Off,  Current time,             1
+
+        Command: sudoku-cli serve
+            Description: Answer solve, count, validate, generate, and grade requests over HTTP with JSON.
+                Long:    --port=value,  --host=value,  --threads=value,  --max-jobs=value,  --timeout-ms=value
+                Short:        -p=value,                       -t=value,
+                Default:          8080,     127.0.0.1,   Number of CPUs,    Number of CPUs,               10000
+
+        Command: sudoku-cli pipe
+            Description: Read one puzzle per line from stdin and write one answer per line to stdout.
+                Long:    --mode=value,  --threads=value
+                Short:                         -t=value
+                Default:        solve,   Number of CPUs
+
+        Command: sudoku-cli convert
+            Description: Convert puzzles between the file formats of other sudoku tools.
+                Long:    --input=value,  --output=value,         --from=value,         --to=value
+                Short:        -i=value,        -o=value,
+                Default:      Required,          stdout,   Input extension/txt,  Output extension/txt
+
+        Command: sudoku-cli render
+            Description: Draw a puzzle as an SVG or PNG image.
+                Long:    --input=value,  --output=value,  --format=value,  --from=value,  --solve,  --candidates,  --size=value,  --font=value
+                Short:        -i=value,        -o=value,        -f=value,                    -s,
+                Default:      Required,          stdout,   Extension/svg,     Extension,      Off,           Off,           450,     Helvetica
+
+        Command: sudoku-cli booklet
+            Description: Lay out puzzles and their answers as a printable PDF booklet.
+                Long:    --input=value,  --generate=value,  --seed=value,  --output=value,         --from=value
+                Short:        -i=value,          -g=value,                       -o=value,
+                Default:      Required,              None,  Current time,        Required,  Input extension/txt
+                Long:    --per-page=value,  --title=value,  --paper=value,  --threads=value
+                Short:                                                            -t=value
+                Default:                4,         Sudoku,          letter,   Number of CPUs
+
+        Command: sudoku-cli import-image
+            Description: Read a puzzle from a PNG or JPEG image of a grid.
+                Long:    --input=value,  --output=value,  --min-confidence=value,  --threads=value
+                Short:        -i=value,        -o=value,                              -t=value
+                Default:      Required,            None,                     0.8,   Number of CPUs
+
+        Command: sudoku-cli bench
+            Description: Time counting the solutions of puzzle files across engines, thread counts, and heuristics.
+                Long:    --input=value,         --threads=value,  --engines=value,  --heuristics=value,  --seed=value
+                Short:        -i=value,                -t=value,
+                Default:    easy, hard,   1 and number of CPUs,             masks,                 mrv,              0
+                Long:    --warmup=value,  --repetitions=value,  --from=value,          --json=value
+                Short:         -w=value,              -r=value,
+                Default:                1,                   5,  Extension/txt,                 None
+
+        Command: sudoku-cli verify
+            Description: Check a proposed solution against a puzzle and show every square that breaks a rule.
+                Long:    --input=value,  --solution=value
+                Short:        -i=value,          -s=value
+                Default:      Required,          Required
+
+        Command: sudoku-cli merge
+            Description: Combine the outputs of count-all or find-all runs with --shard into one result.
+                Long:    --input=value,  --mode=value,  --output=value
+                Short:        -i=value,                       -o=value
+                Default:      Required,        counts,        Terminal
+
+        Command: sudoku-cli estimate
+            Description: Estimate the number of solutions from random walks down the search tree.
+                Long:    --input=value,  --threads=value,  --samples=value,  --target-error=value,  --max-samples=value
+                Short:        -i=value,         -t=value,         -n=value,
+                Default:      Required,   Number of CPUs,             10000,                  None,             10000000
+                Long:    --heuristic=value,  --seed=value
+                Default:               mrv,  Current time
+
+        Command: sudoku-cli sample
+            Description: Sample solutions at random, each about as likely as any other.
+                Long:    --input=value,  --threads=value,  --n=value,  --seed=value,  --probes=value,  --output=value,  --compact
+                Short:        -i=value,         -t=value,   -n=value,                                     -o=value,         -c
+                Default:      Required,   Number of CPUs,          1,  Current time,            1000,        Terminal,        Off
+
+        Command: sudoku-cli analyze
+            Description: Show which empty squares have the same value in every solution, and the values of the others.
+                Long:    --input=value,  --threads=value
+                Short:        -i=value,         -t=value
+                Default:      Required,   Number of CPUs
+
+        Command: sudoku-cli fix-unique
+            Description: Find the fewest clues to add to a puzzle with several solutions so that it has exactly one.
+                Long:    --input=value,  --threads=value,  --symmetry=value
+                Short:        -i=value,         -t=value,
+                Default:      Required,   Number of CPUs,              none
+
+        Command: sudoku-cli explain-unsolvable
+            Description: Find a minimal set of givens that already has no solution, for puzzles without one.
+                Long:    --input=value,  --threads=value
+                Short:        -i=value,         -t=value
+                Default:      Required,   Number of CPUs
+
+        Command: sudoku-cli replay
+            Description: Watch a search recorded with --trace play back in the terminal.
+                Long:    --input=value,  --ms-per-frame=value
+                Short:        -i=value,              -m=value
+                Default:      Required,                    50
+
+INPUT:
+
+    If your input is -, sudoku-cli will read from stdin. Only one input of a command can be -.
+    If your input is a valid file path, sudoku-cli will read from the file.
     Otherwise it treats the input value as a string.
 
     sudoku-cli reads the first 81 non-whitespace characters from the input file or string. 
@@ -150,6 +914,221 @@ INPUT:
                  - 5 - 3 - 4 6 9 8
                  8 - - - - - 3 1 -"
 
+HEURISTICS:
+
+    find-one, find-all, watch-one, watch-all, and count-all accept --heuristic=value and --seed=value,
+    which choose how the search picks the next square to fill and the order in which it tries values:
+
+        mrv           The square with the fewest options; the first one found wins ties. (default)
+        mrv-degree    The square with the fewest options; ties go to the square with the most empty peers.
+        mrv-random    The square with the fewest options; a random one wins ties. Values in a random order.
+        lcv           Squares as mrv; values that remove the fewest options from empty peers go first.
+        random        A random square and a random order of values.
+        row-major     The first empty square from left to right, top to bottom.
+
+    Randomized heuristics are reproducible with --seed=value and seeded from the current time otherwise.
+    Randomized heuristics print the seed they use so a run can be repeated. find-one --random draws one
+    solution the same way as sample, so every solution of the puzzle is about as likely as any other.
+
+TRACE:
+
+    find-one, find-all, and count-all accept --trace=path/to/trace.jsonl, which runs the search on a single
+    thread and records every fill, clear, and solution as one line of JSON with its depth and timestamp.
+    The replay command reads a trace back and animates it the same way as the watch commands.
+
+    They also accept --tree=path/to/tree.dot, which writes the branching structure of a single-threaded
+    search as a Graphviz graph: the square chosen at each node, the values tried, dead ends, and solutions.
+    Nodes deeper than --tree-depth are summarized in the label of their ancestor.
+
+ENGINES:
+
+    find-one, find-all, and count-all accept --engine=value, which chooses how the board keeps track of options:
+
+        masks         Row, column, and box masks of the values present; options are recomputed when needed. (default)
+        candidates    The candidates of every square, updated once for the 20 peers of each filled square.
+
+    Both engines find the same solutions. The candidates engine always branches like mrv, so it cannot be
+    combined with --heuristic, --trace, or --tree. bench --engines=masks,candidates compares the two.
+
+EQUIVALENCE:
+
+    Two puzzles are equivalent if one can be turned into the other by relabeling digits, swapping rows within
+    a band, swapping bands, swapping columns within a stack, swapping stacks, or transposing the grid.
+    Rotations and reflections are combinations of these. The canon command prints the smallest puzzle string
+    among all equivalent puzzles, with digits relabeled in order of first appearance, so equivalent puzzles
+    always have the same canonical form.
+
+TRANSFORMS:
+
+    The transform command applies its options in this order, to the puzzle and to --solution if given:
+
+        --rows=102345678      Reorder rows; row i becomes the row at position i of the order. Rows stay in their bands.
+        --bands=201           Reorder bands of three rows the same way.
+        --transpose           Swap rows and columns.
+        --reflect=value       Mirror across a line: horizontal, vertical, diagonal, or anti-diagonal.
+        --rotate=value        Rotate clockwise by a number of quarter turns.
+        --relabel=912345678   Replace each digit d with the digit at position d of the relabeling.
+        --random              Finish with a random transpose, band, row, stack, and column order, and relabeling.
+
+    Every transform keeps the puzzle valid and equivalent to the original, so the transformed solution
+    is the solution of the transformed puzzle and nothing needs to be solved again.
+
+SERVER:
+
+    The serve command answers POST requests with JSON bodies, so a frontend can reuse one process:
+
+        /solve      {"puzzle": "...", "heuristic": "mrv", "seed": 7}  ->  {"solved": true, "solution": "..."}
+        /count      {"puzzle": "..."}                                 ->  {"count": 35}
+        /validate   {"puzzle": "..."}                                 ->  {"status": "unique", "clues": 30, "solutions": [...]}
+        /generate   {"seed": 7}                                       ->  {"seed": 7, "puzzle": "...", "solution": "...", "clues": 24}
+        /grade      {"puzzle": "..."}                                 ->  {"grade": "hard", "guesses": 14, "fills": 211}
+
+    Every request runs on one shared pool of --threads threads. At most --max-jobs requests are solved at once,
+    and further requests are answered with 503 until one finishes. A request that takes longer than
+    --timeout-ms is answered with 504 and its search is cancelled, freeing its place among --max-jobs as soon
+    as the search stops. A request only takes a place once its whole body has arrived.
+    Grades are easy (no guesses), medium (up to 10), hard (up to 100), or expert (more than 100), where a guess
+    is a square the search fills while it still has more than one option.
+
+PIPE:
+
+    The pipe command reads one puzzle per line from stdin and writes one answer per line to stdout as soon
+    as it is found, without clearing the screen or drawing boards, so it fits in a pipeline:
+
+        solve    The solution as 81 digits, or unsolvable.
+        count    The number of solutions.
+        check    unique and the solution, multiple and two solutions, or unsolvable.
+        canon    The canonical form.
+        grade    The grade and the number of guesses, or unsolvable or multiple.
+
+FORMATS:
+
+    The convert command reads and writes these formats, chosen by --from and --to or by file extension:
+
+        sdk       SadMan Sudoku: a [Puzzle] section of 9 rows and an optional [State] section. One puzzle per file.
+        sdm       SadMan Sudoku multi-puzzle: one puzzle of 81 digits per line, with 0 for each empty square.
+        ss        Simple Sudoku: 9 rows such as .75|...|..4, with ---+---+--- between bands.
+        hodoku    HoDoKu: one puzzle per line, with + before each value filled in after the givens. (no extension)
+        csv       9 rows of 9 comma-separated values, with an empty value for each empty square.
+        txt       The input format of sudoku-cli, one puzzle per line or grids of 9 rows between blank lines. (default)
+
+    sdk and hodoku keep givens apart from values filled in later. The other formats write every value as a given.
+
+BOOKLETS:
+
+    The booklet command prints --per-page puzzles on each page under --title, labeled with their number and grade,
+    followed by an answer key with the solution of every puzzle in the same order. It reads puzzles from --input,
+    one per line by default, or generates --generate=count new puzzles from --seed. Every puzzle must have
+    exactly one solution. Pages are numbered at the bottom and are letter or a4 size, which hold at most 15
+    and 18 puzzles per page. The title is drawn in the built-in Helvetica fonts, which cover Latin-1 letters
+    and common punctuation but not other scripts.
+
+IMAGES:
+
+    The import-image command finds the grid in a screenshot, scan, or photo, reads the digit in each of the
+    81 cells, and prints the puzzle as a string that every other command accepts as --input. The grid must be
+    the largest connected shape in the image; mild rotation and perspective are fine. Digits are compared
+    with templates built from the same stroke font the render command uses, so no model files are needed.
+    Digits read with less than --min-confidence are listed for checking, and clues that repeat a digit in
+    a row, column, or box are reported as conflicts, since at least one of them must be misread.
+    This is template matching, not general OCR: printed sans-serif digits in cells of about 30 pixels or more
+    read reliably, while serif faces, handwriting, small or blurry grids, and photos with glare or strong
+    perspective can be misread or missed. Check the puzzle before solving it.
+
+BENCHMARKS:
+
+    The bench command counts the solutions of every puzzle in each --input file, once per run, for every
+    combination of --engines, --threads, and --heuristics. Each combination gets its own thread pool, runs --warmup times
+    unmeasured, then --repetitions times measured. The table shows the median and 95th percentile time of the
+    measured runs, the puzzles counted per second, and the speedup over the fewest threads. --json also writes
+    every row along with the time of each run. The candidates engine is timed once per thread count with mrv.
+
+VERIFY:
+
+    The verify command checks that a proposed solution keeps every given of the puzzle, fills all 81 squares,
+    and has no value twice in a row, column, or box. Each broken rule is listed with the coordinates of its
+    squares, from r0c0 at the top left to r8c8 at the bottom right, and those squares are drawn in brackets.
+    verify exits with a non-zero status when the solution breaks any rule, so scripts can check the result.
+
+SHARDS:
+
+    count-all and find-all accept --shard=i/N, which searches only slice i of N disjoint slices of the search,
+    numbered from 0, so N processes on any number of machines can split one long search between them.
+    Every process must be given the same puzzle and N. The merge command then combines their outputs:
+
+        sudoku-cli count-all --input=path/to/puzzle --shard=0/2 > shard0.txt
+        sudoku-cli count-all --input=path/to/puzzle --shard=1/2 > shard1.txt
+        sudoku-cli merge     --input=shard0.txt --input=shard1.txt
+
+    --mode=counts sums the Found: line of each count-all output after checking that together they are every
+    shard from 0 to N - 1 exactly once, of the same puzzle. N can be at most 1024.
+    --mode=solutions joins the files written by find-all --output in the order given and counts the solutions.
+
+CHECKPOINTS:
+
+    count-all accepts --checkpoint=path/to/checkpoint.json, which splits the search into a few hundred branches,
+    counts them one at a time, and rewrites the file with every completed branch and its count after each one.
+    If the run stops, the same command with --resume skips the completed branches and reaches the same total
+    as a run that never stopped. --resume fails if the checkpoint was written for a different puzzle or --shard,
+    and a run without --resume refuses to start over a checkpoint that already exists.
+
+EXACT COUNTS:
+
+    count-all accepts --exact, which counts without visiting the solutions one by one. Squares are filled row by
+    row, and the count from the start of a row only depends on which columns, boxes, and row each digit is in,
+    so each such state is counted once; digits that are not clues are interchangeable, so states that differ by
+    swapping them share a count too. The grid is first transposed and its bands reordered so the bands with the
+    most clues come first. Counts are exact up to 2^128, which holds every completion of the empty grid, but the
+    empty grid itself still has too many states to finish. A full band of clues or 18 scattered ones is enough:
+    a grid with 18 clues and 9,856,430,508 solutions takes under a minute. --exact runs on one thread with its
+    own search, so it cannot be combined with --engine, --heuristic, --seed, or --threads.
+
+ESTIMATES:
+
+    The estimate command walks down the search tree at random, filling the square the heuristic chooses with
+    one of its options picked uniformly, until the board is solved or a square has no options. Each walk is
+    worth the product of the number of options along its path if it ends in a solution and 0 if it gets stuck;
+    the mean of the walks is an unbiased estimate of the number of solutions (Knuth's estimator). The estimate
+    is printed with its 95% confidence interval and the number of walks, which must be at least 2 for the
+    interval to exist. With --target-error, walks continue in batches of 1000 until the half width of the
+    interval is at most that fraction of the estimate.
+    Estimates only depend on the seed and the number of walks, not on the number of threads.
+
+SAMPLES:
+
+    The sample command picks solutions at random without listing them all. While the board has 1000 solutions
+    or more, the empty square with the fewest options is filled with one of them, chosen in proportion to the
+    number of solutions it leaves: counted exactly when there are fewer than 1000, and estimated from --probes
+    random probes otherwise. Once fewer than 1000 remain, they are listed and one is picked uniformly. Puzzles
+    with fewer than 1000 solutions are sampled exactly uniformly; for others, more probes give estimates that
+    are closer, and so samples that are closer to uniform. The same seed always gives the same samples.
+
+ANALYZE:
+
+    The analyze command finds the backbone of a puzzle with more than one solution: the empty squares that have
+    the same value in every solution, drawn in brackets, and the values every other square takes in some
+    solution, drawn in a 3 by 3 block of digits. Only the options of each square are tried, and every solution
+    found marks all 81 of its values at once, so only options no solution has shown yet need a search of
+    their own. The solutions are never listed, so it works on puzzles with far too many solutions to count.
+
+FIX UNIQUE:
+
+    The fix-unique command keeps one solution of a puzzle and adds its values as clues until no other solution
+    is left. It first fills the square with the most options until the puzzle is unique and drops every added
+    clue that is not needed, then looks for a smaller set: each other solution found must differ from the kept
+    one at an added square, so the smallest set of squares that does so for every solution found so far is
+    tried, and any other solutions it misses are used in the next try. The result is marked as the minimum once
+    no smaller set could work; on sparse puzzles it may stop early with a set that is small but not proven minimal.
+    --symmetry=rotational, horizontal, vertical, diagonal, or anti-diagonal adds each clue with its mirror image.
+    The added clues are drawn in brackets.
+
+EXPLAIN UNSOLVABLE:
+
+    The explain-unsolvable command shows why a puzzle has no solution. Givens are removed one at a time in
+    row-major order, keeping each removal that still leaves no solution, so the givens that remain have no
+    solution together but removing any one of them does. They are drawn in brackets: at least one of them is
+    wrong. Givens that repeat a value in a row, column, or box are listed as well.
+
 OUTPUT:
 
     sudkou-cli can write to a new file, or overwrite an existing file; but it will not create a new directory.
@@ -178,6 +1157,100 @@ EXAMPLES:
     sudoku-cli count-all --input=path/to/puzzle
         Count all solutions without writing them to an output.
 
+    sudoku-cli find-one  --input=path/to/puzzle --random --seed=42 --compact
+        Find one solution chosen by seed 42, so every run with the same seed finds the same solution.
+
+    sudoku-cli count-all --input=path/to/puzzle --heuristic=mrv-degree
+        Count all solutions, breaking ties between squares by the number of empty peers.
+
+    sudoku-cli count-all --input=path/to/puzzle --engine=candidates
+        Count all solutions with the engine that keeps the candidates of every square.
+
+    sudoku-cli check-unique --input=path/to/puzzle
+        Check whether a puzzle is proper, printing its solution or two different solutions.
+
+    sudoku-cli minimize  --input=path/to/puzzle --order=random --seed=3
+        List the clues that could be removed on their own, then remove clues in a random order
+        until the puzzle is minimal.
+
+    sudoku-cli canon     --input=path/to/collection --lines --unique
+        Print the canonical form of each puzzle in a file with one puzzle per line, skipping duplicates.
+
+    sudoku-cli same      --first=path/to/puzzle --second=path/to/other/puzzle
+        Check whether two puzzles are the same puzzle up to relabeling, swaps, and transposition.
+
+    sudoku-cli transform --input=path/to/puzzle --solution=path/to/solution --random --seed=7 --count=20 --compact
+        Write 20 random variants of a puzzle, each followed by its solution, on one line each.
+
+    sudoku-cli serve     --port=8080 --max-jobs=4 --timeout-ms=5000
+        Answer JSON requests on http://127.0.0.1:8080, solving at most 4 at once for up to 5 seconds each.
+
+    cat path/to/collection | sudoku-cli pipe --mode=check
+        Check whether each puzzle in a file with one puzzle per line is unique.
+
+    cat path/to/puzzle | sudoku-cli find-one --input=-
+        Find one solution to a puzzle read from stdin.
+
+    sudoku-cli convert   --input=path/to/puzzles.sdm --output=path/to/puzzles.csv
+        Convert a SadMan multi-puzzle file to CSV, choosing both formats by extension.
+
+    sudoku-cli render    --input=path/to/puzzle --output=puzzle.png --size=900 --solve
+        Draw a puzzle and its solution as a 900 by 900 PNG image, with the solution in blue.
+
+    sudoku-cli render    --input=path/to/puzzle --candidates --font="Georgia, serif" > puzzle.svg
+        Draw a puzzle with the candidates of every empty square as an SVG image in a serif font.
+
+    sudoku-cli booklet   --generate=24 --seed=7 --per-page=6 --title="Week 12" --output=week12.pdf
+        Generate 24 puzzles and print them 6 to a page, followed by their answers.
+
+    sudoku-cli booklet   --input=path/to/collection --paper=a4 --output=collection.pdf
+        Print a file with one puzzle per line as an A4 booklet with 4 puzzles on each page.
+
+    sudoku-cli import-image --input=path/to/screenshot.png --output=path/to/puzzle
+        Read a puzzle from a screenshot and write it to a file, listing any digits that need checking.
+
+    sudoku-cli bench     --input=puzzles/hard --input=puzzles/65k --threads=1,2,4 --repetitions=10 --json=bench.json
+        Time counting two puzzle files on 1, 2, and 4 threads, writing the results to a JSON file as well.
+
+    sudoku-cli verify    --input=path/to/puzzle --solution=path/to/solution
+        Check a finished grid against its puzzle and show the squares of every broken rule.
+
+    sudoku-cli find-all  --input=path/to/puzzle --shard=2/4 --compact --output=shard2.txt
+        Find the solutions in the third of four slices of the search and write them to a file.
+
+    sudoku-cli merge     --input=shard0.txt --input=shard1.txt --input=shard2.txt --input=shard3.txt --mode=solutions
+        Join the solutions of four shards and print them with their total count.
+
+    sudoku-cli count-all --input=path/to/puzzle --checkpoint=count.json --resume
+        Continue a count that was stopped, starting after the last branch recorded in count.json.
+
+    sudoku-cli count-all --input=path/to/puzzle --exact
+        Count the solutions of a puzzle with few clues, remembering the count of each state it reaches.
+
+    sudoku-cli estimate  --input=path/to/puzzle --target-error=0.01 --seed=5
+        Estimate the number of solutions to within 1%, printing the estimate with its 95% confidence interval.
+
+    sudoku-cli sample    --input=path/to/puzzle --n=100 --seed=9 --compact --output=samples.txt
+        Write 100 random solutions of a puzzle to a file, one per line, the same ones on every run with seed 9.
+
+    sudoku-cli analyze   --input=path/to/puzzle
+        Show the squares that every solution agrees on and the values each other square can take.
+
+    sudoku-cli fix-unique --input=path/to/puzzle --symmetry=rotational
+        Add the fewest pairs of clues, opposite each other across the center, that make a puzzle unique.
+
+    sudoku-cli explain-unsolvable --input=path/to/puzzle
+        Show a smallest group of givens that can not all be right, for a puzzle without a solution.
+
+    sudoku-cli find-all  --input=path/to/puzzle --trace=trace.jsonl
+        Find all solutions on a single thread and record every step of the search to a trace.
+
+    sudoku-cli count-all --input=path/to/puzzle --tree=tree.dot --tree-depth=4
+        Count all solutions on a single thread and write the top 4 levels of the search tree to a DOT graph.
+
+    sudoku-cli replay    --input=trace.jsonl --ms-per-frame=5
+        Play back a recorded trace at 5 milliseconds per frame.
+
 MORE:
 
     For more details on each subcommand, use the help command:
@@ -187,6 +1260,26 @@ MORE:
         sudoku-cli help watch-one
         sudoku-cli help watch-all
         sudoku-cli help count-all
+        sudoku-cli help check-unique
+        sudoku-cli help minimize
+        sudoku-cli help canon
+        sudoku-cli help same
+        sudoku-cli help transform
+        sudoku-cli help serve
+        sudoku-cli help pipe
+        sudoku-cli help convert
+        sudoku-cli help render
+        sudoku-cli help booklet
+        sudoku-cli help import-image
+        sudoku-cli help bench
+        sudoku-cli help verify
+        sudoku-cli help merge
+        sudoku-cli help estimate
+        sudoku-cli help sample
+        sudoku-cli help analyze
+        sudoku-cli help fix-unique
+        sudoku-cli help explain-unsolvable
+        sudoku-cli help replay
 "#;
 #[derive(StructOpt, Debug)]
 #[structopt(name = "sudoku-cli", about = ABOUT)]
@@ -203,16 +1296,187 @@ fn build_thread_pool(threads: Option<usize>) -> Result<(), SudokuError> {
         .map_err(SudokuError::from)
 }
 
+/// Whether an input has already read stdin to its end.
+static STDIN_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Claims stdin for one input, failing if another input already read it,
+/// so that a second `-` is an error instead of an empty puzzle.
+fn take_stdin() -> Result<io::Stdin, SudokuError> {
+    if STDIN_TAKEN.swap(true, Ordering::SeqCst) {
+        return Err(IOError(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only one input can be read from stdin with -",
+        )));
+    }
+    Ok(io::stdin())
+}
+
 fn puzzle_input(input: &str) -> Result<String, SudokuError> {
-    if Path::new(input).exists() {
+    if "-" == input {
+        let mut puzzle = String::new();
+        take_stdin()?.read_to_string(&mut puzzle)?;
+        Ok(puzzle)
+    } else if Path::new(input).exists() {
         fs::read_to_string(input).map_err(SudokuError::from)
     } else {
         Ok(String::from(input))
     }
 }
 
-fn print_count(count: usize) {
-    if 1 == count {
+/// Searches sequentially, recording every step to a trace file and/or a search tree if requested.  
+/// Returns the number of solutions found along with each solution formatted as a String.
+fn find_observed(
+    board: &mut SudokuBoard,
+    find_all: bool,
+    compact: bool,
+    trace: &Option<String>,
+    tree: &Option<String>,
+    tree_depth: usize,
+) -> Result<(usize, String), SudokuError> {
+    let mut trace = match trace {
+        Some(path) => Some(Trace::create(path, &board.to_string_puzzle())?),
+        None => None,
+    };
+    let mut search_tree = SearchTree::new(tree_depth);
+    let mut solutions = String::new();
+    let count = board.search_observed(find_all, &mut |board, event| {
+        if let SearchEvent::Solution { .. } = event {
+            if compact {
+                solutions += &board.to_string_compact();
+            } else {
+                solutions += &board.to_string();
+            }
+        }
+        if let Some(trace) = trace.as_mut() {
+            trace.record(event);
+        }
+        if tree.is_some() {
+            search_tree.record(event);
+        }
+    });
+    if let Some(trace) = trace {
+        trace.finish()?;
+    }
+    if let Some(path) = tree {
+        let mut file = BufWriter::new(File::create(path)?);
+        search_tree.write_dot(&mut file)?;
+        file.flush()?;
+    }
+    Ok((count, solutions))
+}
+
+/// Reads a puzzle for the candidates engine, failing if it was combined with options only the masks engine has.
+fn load_candidates(
+    input: &str,
+    heuristic: &str,
+    is_observed: bool,
+) -> Result<CandidateBoard, SudokuError> {
+    check_candidates(heuristic, is_observed)?;
+    Ok(CandidateBoard::from(puzzle_input(input)?))
+}
+
+/// Fails if the candidates engine was combined with options only the masks engine has.
+fn check_candidates(heuristic: &str, is_observed: bool) -> Result<(), SudokuError> {
+    if "mrv" != heuristic || is_observed {
+        return Err(IOError(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the candidates engine only searches with the mrv heuristic and cannot record --trace or --tree",
+        )));
+    }
+    Ok(())
+}
+
+/// Reads a puzzle and returns it with the branches its search is split into, only those of a shard if given.
+fn load_branches(input: &str, shard: Option<Shard>) -> Result<(String, Vec<String>), SudokuError> {
+    let board = SudokuBoard::from(puzzle_input(input)?);
+    let puzzle = board.to_string_puzzle();
+    let branches = checkpoint::branches(&puzzle, shard);
+    println!("\n{}", board);
+    match shard {
+        Some(shard) => {
+            println!("  Puzzle: {}", puzzle);
+            println!("  Shard: {} with {} branches\n", shard, branches.len());
+        }
+        None => println!("  Branches: {}\n", branches.len()),
+    }
+    Ok((puzzle, branches))
+}
+
+/// Formats solutions found by the candidates engine the same way as the masks engine.
+fn format_solutions(solutions: &[String], compact: bool) -> String {
+    solutions
+        .iter()
+        .map(|solution| {
+            if compact {
+                format!("{}\n", solution)
+            } else {
+                SudokuBoard::from(solution.as_str()).to_string()
+            }
+        })
+        .collect()
+}
+
+/// Reads a puzzle from the input and sets the heuristic the search will use.
+fn load_board(input: &str, heuristic: &str, seed: u64) -> Result<SudokuBoard, SudokuError> {
+    let heuristic = heuristic::by_name(heuristic, seed).expect("heuristic is one of HEURISTICS");
+    Ok(SudokuBoard::from(puzzle_input(input)?).with_heuristic(heuristic))
+}
+
+/// Returns the seed for a heuristic, printing it if the heuristic is randomized so the run can be repeated.
+fn heuristic_seed(heuristic: &str, seed: Option<u64>) -> u64 {
+    let seed = seed_or_now(seed);
+    if heuristic::is_randomized(heuristic) {
+        println!("\n  Seed:  {}", seed);
+    }
+    seed
+}
+
+/// Returns the given seed, or a seed taken from the current time.
+fn seed_or_now(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default()
+    })
+}
+
+/// Formats the clues at the given indices of a puzzle string as `r{row}c{col}={value}`.
+fn format_clues(puzzle: &str, indices: &[usize]) -> String {
+    let clues: Vec<String> = indices
+        .iter()
+        .map(|&index| format!("r{}c{}={}", index / 9, index % 9, &puzzle[index..=index]))
+        .collect();
+    clues.join(", ")
+}
+
+/// Writes solutions to a file if there is an output path, or prints them otherwise, along with their count.
+fn print_solutions(
+    count: usize,
+    solutions: &str,
+    output: Option<String>,
+    elapsed: Duration,
+) -> Result<(), SudokuError> {
+    if let Some(path) = output {
+        let mut file = File::create(&path)?;
+        file.write_all(solutions.as_bytes())?;
+        print_count(count);
+        println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        if 1 == count {
+            println!("  Writing solution to file: {}\n\n", path);
+        } else {
+            println!("  Writing solutions to file: {}\n\n", path);
+        }
+    } else {
+        println!("  Solutions:\n\n{}", solutions);
+        print_count(count);
+        println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+    }
+    Ok(())
+}
+
+fn print_count<T: fmt::Display + PartialEq + From<u8>>(count: T) {
+    if T::from(1) == count {
         println!("  Found: 1 solution\n");
     } else {
         println!("  Found: {} solutions\n", count);
@@ -220,32 +1484,783 @@ fn print_count(count: usize) {
 }
 
 fn main() -> Result<(), SudokuError> {
-    println!("{}", ClearScreen);
-    match Opt::from_args().action {
+    let action = Opt::from_args().action;
+    if !matches!(
+        action,
+        Action::Pipe(_) | Action::Convert(_) | Action::Render(_)
+    ) {
+        println!("{}", ClearScreen);
+    }
+    match action {
         Action::WatchOne(opts) => {
-            let mut board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let mut board = load_board(
+                &opts.input,
+                &opts.heuristic,
+                heuristic_seed(&opts.heuristic, opts.seed),
+            )?;
             board.watch_find_one(opts.ms_per_frame)
         }
         Action::WatchAll(opts) => {
-            let mut board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let mut board = load_board(
+                &opts.input,
+                &opts.heuristic,
+                heuristic_seed(&opts.heuristic, opts.seed),
+            )?;
             board.watch_find_all(opts.ms_per_frame)
         }
-        Action::CountAll(opts) => {
+        Action::Replay(opts) => {
+            let (puzzle, events) = if "-" == opts.input {
+                read_trace_from(take_stdin()?.lock())?
+            } else {
+                read_trace(&opts.input)?
+            };
+            let mut board = SudokuBoard::from(puzzle);
+            board.watch_replay(&events, opts.ms_per_frame)
+        }
+        Action::CheckUnique(opts) => {
             build_thread_pool(opts.threads)?;
             let mut board = SudokuBoard::from(puzzle_input(&opts.input)?);
             println!("\n{}", board);
             let now = Instant::now();
+            let uniqueness = board.check_unique();
+            let elapsed = now.elapsed();
+            let (status, solutions) = match uniqueness {
+                Uniqueness::Unsolvable => ("unsolvable, 0 solutions", vec![]),
+                Uniqueness::Unique(solution) => ("unique, 1 solution", vec![solution]),
+                Uniqueness::Multiple(first, second) => {
+                    ("not unique, at least 2 solutions", vec![first, second])
+                }
+            };
+            if !solutions.is_empty() {
+                println!("  Solutions:\n");
+            }
+            for solution in solutions {
+                if opts.compact {
+                    println!("{}", solution);
+                } else {
+                    print!("{}", SudokuBoard::from(solution).to_string());
+                }
+            }
+            println!("\n  Status: {}\n", status);
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::Minimize(opts) => {
+            build_thread_pool(opts.threads)?;
+            let board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            println!("\n{}", board);
+            let puzzle = board.to_string_puzzle();
+            let now = Instant::now();
+            let order = minimize::clue_order(&puzzle, &opts.order, seed_or_now(opts.seed))
+                .expect("order is one of CLUE_ORDERS");
+            match (
+                minimize::redundant_clues(&puzzle),
+                minimize::minimize(&puzzle, &order),
+            ) {
+                (Some(redundant), Some(minimal)) => {
+                    let elapsed = now.elapsed();
+                    println!("  Clues:     {}", minimize::clues(&puzzle).len());
+                    println!(
+                        "  Redundant: {} [{}]\n",
+                        redundant.len(),
+                        format_clues(&puzzle, &redundant)
+                    );
+                    println!("  Minimized:\n\n{}", SudokuBoard::from(minimal.as_str()));
+                    println!("  Clues:     {}", minimize::clues(&minimal).len());
+                    println!("  Puzzle:    {}\n", minimal);
+                    println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+                }
+                _ => {
+                    println!("  Status: only a puzzle with exactly one solution can be minimized\n")
+                }
+            }
+        }
+        Action::Canon(opts) => {
+            build_thread_pool(opts.threads)?;
+            let input = puzzle_input(&opts.input)?;
+            if opts.lines {
+                let mut seen = HashSet::new();
+                for line in input.lines().filter(|line| !line.trim().is_empty()) {
+                    let canonical = canon::canonical_form(&SudokuBoard::from(line));
+                    if !opts.unique || seen.insert(canonical.clone()) {
+                        println!("{}", canonical);
+                    }
+                }
+                return Ok(());
+            }
+            let board = SudokuBoard::from(input);
+            println!("\n{}", board);
+            let now = Instant::now();
+            let canonical = canon::canonical_form(&board);
+            let elapsed = now.elapsed();
+            println!("  Canonical:\n\n{}", SudokuBoard::from(canonical.as_str()));
+            println!("  Puzzle:  {}\n", canonical);
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::Same(opts) => {
+            build_thread_pool(opts.threads)?;
+            let first = SudokuBoard::from(puzzle_input(&opts.first)?);
+            let second = SudokuBoard::from(puzzle_input(&opts.second)?);
+            let now = Instant::now();
+            let first_canonical = canon::canonical_form(&first);
+            let second_canonical = canon::canonical_form(&second);
+            let elapsed = now.elapsed();
+            println!("\n  First:   {}", first_canonical);
+            println!("  Second:  {}\n", second_canonical);
+            if first_canonical == second_canonical {
+                println!("  Status: the puzzles are equivalent\n");
+            } else {
+                println!("  Status: the puzzles are not equivalent\n");
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::Transform(opts) => {
+            let puzzle = SudokuBoard::from(puzzle_input(&opts.input)?).to_string_puzzle();
+            let solution = match &opts.solution {
+                Some(input) => Some(SudokuBoard::from(puzzle_input(input)?).to_string_puzzle()),
+                None => None,
+            };
+            if let Some(solution) = &solution {
+                let agrees = puzzle
+                    .chars()
+                    .zip(solution.chars())
+                    .all(|(clue, value)| '.' != value && ('.' == clue || clue == value));
+                if !agrees {
+                    println!(
+                        "  Status: the solution must fill every square and agree with the puzzle\n"
+                    );
+                    return Ok(());
+                }
+            }
+            let mut fixed = transform::Transform::identity();
+            if let Some(rows) = opts.rows {
+                fixed = fixed.then(&transform::Transform::permute_rows(rows));
+            }
+            if let Some(bands) = opts.bands {
+                fixed = fixed.then(&transform::Transform::permute_bands(bands));
+            }
+            if opts.transpose {
+                fixed = fixed.then(&transform::Transform::transpose());
+            }
+            if let Some(reflect) = &opts.reflect {
+                let reflection =
+                    Reflection::from_name(reflect).expect("reflect is one of REFLECTIONS");
+                fixed = fixed.then(&transform::Transform::reflect(reflection));
+            }
+            if let Some(quarter_turns) = opts.rotate {
+                fixed = fixed.then(&transform::Transform::rotate(quarter_turns));
+            }
+            if let Some(digits) = opts.relabel {
+                fixed = fixed.then(&transform::Transform::relabel(digits));
+            }
+            let seed = seed_or_now(opts.seed);
+            if opts.random {
+                println!("\n  Seed:  {}", seed);
+            }
+            let mut rng = SplitMix64::new(seed);
+            let count = opts.count.unwrap_or(1);
+            let mut variants = String::new();
+            for _ in 0..count {
+                let transform = if opts.random {
+                    fixed.then(&transform::Transform::random(&mut rng))
+                } else {
+                    fixed.clone()
+                };
+                let mut grids = vec![transform.apply(&puzzle)];
+                grids.extend(solution.iter().map(|solution| transform.apply(solution)));
+                if opts.compact {
+                    variants += &grids.join(" ");
+                    variants.push('\n');
+                } else {
+                    for grid in grids {
+                        variants += &SudokuBoard::from(grid).to_string();
+                    }
+                }
+            }
+            if let Some(path) = opts.output {
+                let mut file = File::create(&path)?;
+                file.write_all(variants.as_bytes())?;
+                if 1 == count {
+                    println!("\n  Writing 1 variant to file: {}\n\n", path);
+                } else {
+                    println!("\n  Writing {} variants to file: {}\n\n", count, path);
+                }
+            } else {
+                println!("\n{}", variants);
+            }
+        }
+        Action::Serve(opts) => {
+            let threads = opts.threads.unwrap_or_else(num_cpus::get);
+            let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+            let config = server::Config {
+                address: format!("{}:{}", opts.host, opts.port),
+                max_jobs: opts.max_jobs.unwrap_or_else(num_cpus::get).max(1),
+                timeout: Duration::from_millis(opts.timeout_ms),
+            };
+            server::serve(config, Arc::new(pool))?;
+        }
+        Action::Pipe(opts) => {
+            build_thread_pool(opts.threads)?;
+            pipe::run(&opts.mode, io::stdin().lock(), &mut io::stdout().lock())?;
+        }
+        Action::Convert(opts) => {
+            let from = opts
+                .from
+                .as_deref()
+                .or_else(|| formats::detect(&opts.input));
+            let to = opts
+                .to
+                .as_deref()
+                .or_else(|| opts.output.as_deref().and_then(formats::detect));
+            let (from, to) = (from.unwrap_or("txt"), to.unwrap_or("txt"));
+            let grids = formats::read(from, &puzzle_input(&opts.input)?)?;
+            if !formats::keeps_filled_values(to) && grids.iter().any(Grid::has_filled_values) {
+                eprintln!(
+                    "  Note: {} cannot mark filled-in values, so they are written as givens",
+                    to
+                );
+            }
+            let text = formats::write(to, &grids)?;
+            match opts.output {
+                Some(path) => fs::write(path, text)?,
+                None => io::stdout().write_all(text.as_bytes())?,
+            }
+        }
+        Action::Render(opts) => {
+            let from_output = opts.output.as_deref().and_then(|path| {
+                let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+                RENDER_FORMATS
+                    .iter()
+                    .find(|&&format| format == extension)
+                    .copied()
+            });
+            let format = opts.format.as_deref().or(from_output).unwrap_or("svg");
+            if "png" == format && opts.font.is_some() {
+                return Err(IOError(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--font only applies to svg images; png images use the built-in stroke font",
+                )));
+            }
+            let text = puzzle_input(&opts.input)?;
+            let mut grid = match opts
+                .from
+                .as_deref()
+                .or_else(|| formats::detect(&opts.input))
+            {
+                Some(from) => formats::read(from, &text)?.remove(0),
+                None => Grid::new(&SudokuBoard::from(text).to_string_puzzle()),
+            };
+            if opts.solve {
+                match SudokuBoard::from(grid.givens.as_str()).find_up_to(1).pop() {
+                    Some(solution) => grid = Grid::with_values(&grid.givens, &solution),
+                    None => {
+                        eprintln!("  Note: the puzzle has no solution, so it is drawn unsolved")
+                    }
+                }
+            }
+            let style = Style {
+                size: opts.size,
+                font: opts.font.unwrap_or_else(|| Style::default().font),
+                candidates: opts.candidates,
+            };
+            let image = render::render(format, &grid, &style)?;
+            match opts.output {
+                Some(path) => fs::write(path, image)?,
+                None => io::stdout().write_all(&image)?,
+            }
+        }
+        Action::Booklet(opts) => {
+            build_thread_pool(opts.threads)?;
+            let now = Instant::now();
+            let entries = match (opts.input, opts.generate) {
+                (Some(input), _) => {
+                    let from = opts
+                        .from
+                        .as_deref()
+                        .or_else(|| formats::detect(&input))
+                        .unwrap_or("txt");
+                    let grids = formats::read(from, &puzzle_input(&input)?)?;
+                    let puzzles: Vec<String> = grids.into_iter().map(|grid| grid.givens).collect();
+                    booklet::entries(&puzzles)?
+                }
+                (None, count) => {
+                    let seed = seed_or_now(opts.seed);
+                    println!("\n  Seed:  {}", seed);
+                    booklet::generated(count.unwrap_or_default(), seed)
+                }
+            };
+            let options = booklet::Options {
+                title: opts.title,
+                per_page: opts.per_page,
+                paper: opts.paper,
+            };
+            let pdf = booklet::booklet(&entries, &options)?;
+            fs::write(&opts.output, pdf.to_bytes())?;
+            println!("\n  Puzzles: {}", entries.len());
+            println!("  Pages:   {}", pdf.len());
+            println!("  Time:    {} seconds\n", now.elapsed().as_secs_f64());
+            println!("  Writing booklet to file: {}\n\n", opts.output);
+        }
+        Action::ImportImage(opts) => {
+            build_thread_pool(opts.threads)?;
+            let bytes = if "-" == opts.input {
+                let mut bytes = Vec::new();
+                take_stdin()?.read_to_end(&mut bytes)?;
+                bytes
+            } else {
+                fs::read(&opts.input)?
+            };
+            let now = Instant::now();
+            let recognition = recognize::recognize(&recognize::decode(&bytes)?)?;
+            let elapsed = now.elapsed();
+            let puzzle = recognition.puzzle();
+            println!("\n{}", SudokuBoard::from(puzzle.as_str()));
+            println!("  Puzzle: {}", puzzle);
+            println!("  Clues:  {}", minimize::clues(&puzzle).len());
+            let lowest = recognition
+                .cells
+                .iter()
+                .map(|cell| cell.confidence)
+                .fold(1.0, f64::min);
+            println!("  Lowest confidence: {:.2}", lowest);
+            let uncertain: Vec<String> = recognition
+                .uncertain(opts.min_confidence)
+                .iter()
+                .map(|&index| {
+                    let confidence = recognition.cells[index].confidence;
+                    format!("{} ({:.2})", format_clues(&puzzle, &[index]), confidence)
+                })
+                .collect();
+            if !uncertain.is_empty() {
+                println!("  Uncertain: {}", uncertain.join(", "));
+            }
+            let conflicts: Vec<String> = recognize::conflicts(&puzzle)
+                .iter()
+                .map(|&(first, second)| format_clues(&puzzle, &[first, second]))
+                .collect();
+            if conflicts.is_empty() {
+                println!("  Conflicts: none");
+            } else {
+                println!("  Conflicts: {}", conflicts.join("; "));
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+            if let Some(path) = opts.output {
+                fs::write(&path, puzzle + "\n")?;
+                println!("  Writing puzzle to file: {}\n\n", path);
+            }
+        }
+        Action::Bench(opts) => {
+            let inputs = if opts.inputs.is_empty() {
+                vec![String::from("puzzles/easy"), String::from("puzzles/hard")]
+            } else {
+                opts.inputs
+            };
+            let mut corpora = Vec::new();
+            for input in inputs {
+                let from = opts
+                    .from
+                    .as_deref()
+                    .or_else(|| formats::detect(&input))
+                    .unwrap_or("txt");
+                let grids = formats::read(from, &fs::read_to_string(&input)?)?;
+                corpora.push(bench::Corpus {
+                    name: input,
+                    puzzles: grids.into_iter().map(|grid| grid.givens).collect(),
+                });
+            }
+            let mut threads = if opts.threads.is_empty() {
+                vec![1, num_cpus::get()]
+            } else {
+                opts.threads
+            };
+            threads.sort_unstable();
+            threads.dedup();
+            let config = bench::Config {
+                engines: opts.engines,
+                threads,
+                heuristics: opts.heuristics,
+                seed: opts.seed,
+                warmup: opts.warmup,
+                repetitions: opts.repetitions,
+            };
+            let measurements = bench::run(&corpora, &config, |measurement| {
+                println!(
+                    "  Timed {} with {} and {} on {} {}",
+                    measurement.corpus,
+                    measurement.engine,
+                    measurement.heuristic,
+                    measurement.threads,
+                    if 1 == measurement.threads {
+                        "thread"
+                    } else {
+                        "threads"
+                    }
+                );
+            })?;
+            println!("\n{}", bench::table(&measurements));
+            if let Some(path) = opts.json {
+                fs::write(&path, format!("{:#}\n", bench::json(&measurements)))?;
+                println!("  Writing results to file: {}\n\n", path);
+            }
+        }
+        Action::Verify(opts) => {
+            if "-" == opts.input && "-" == opts.solution {
+                return Err(IOError(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--input and --solution cannot both be read from stdin",
+                )));
+            }
+            let puzzle = SudokuBoard::from(puzzle_input(&opts.input)?).to_string_puzzle();
+            let solution = SudokuBoard::from(puzzle_input(&opts.solution)?);
+            let now = Instant::now();
+            let violations = verify::verify(&puzzle, &solution.to_string_puzzle());
+            let elapsed = now.elapsed();
+            println!(
+                "{}",
+                solution.to_string_highlighted(&verify::highlighted(&violations))
+            );
+            println!("  Puzzle:   {}", puzzle);
+            println!("  Solution: {}\n", solution.to_string_puzzle());
+            if violations.is_empty() {
+                println!("  Status: the solution is correct\n");
+            } else {
+                if 1 == violations.len() {
+                    println!("  Status: the solution breaks 1 rule, shown in brackets\n");
+                } else {
+                    println!(
+                        "  Status: the solution breaks {} rules, shown in brackets\n",
+                        violations.len()
+                    );
+                }
+                for violation in &violations {
+                    println!("    {}", violation);
+                }
+                println!();
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+            if !violations.is_empty() {
+                return Err(IOError(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the solution is not correct",
+                )));
+            }
+        }
+        Action::Merge(opts) => {
+            let now = Instant::now();
+            let mut outputs = Vec::with_capacity(opts.inputs.len());
+            for input in &opts.inputs {
+                outputs.push(fs::read_to_string(input)?);
+            }
+            let (count, merged) = if "solutions" == opts.mode {
+                let solutions = outputs.concat();
+                (shard::count_solutions_in(&solutions), solutions)
+            } else {
+                let mut counts = Vec::with_capacity(outputs.len());
+                for (input, output) in opts.inputs.iter().zip(&outputs) {
+                    counts.push(shard::parse_shard_count(output).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("no shard, puzzle, and count found in {}", input),
+                        )
+                    })?);
+                }
+                let count = shard::total(&counts)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                (count, format!("{}\n", count))
+            };
+            let elapsed = now.elapsed();
+            println!("\n  Shards: {}\n", opts.inputs.len());
+            print_count(count);
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+            if let Some(path) = opts.output {
+                fs::write(&path, merged)?;
+                let merged = if "solutions" == opts.mode {
+                    "solutions"
+                } else {
+                    "count"
+                };
+                println!("  Writing {} to file: {}\n\n", merged, path);
+            } else if "solutions" == opts.mode {
+                println!("  Solutions:\n\n{}", merged);
+            }
+        }
+        Action::Estimate(opts) => {
+            build_thread_pool(opts.threads)?;
+            let seed = seed_or_now(opts.seed);
+            let board = load_board(&opts.input, &opts.heuristic, seed)?;
+            println!("\n{}", board);
+            println!("  Seed:  {}\n", seed);
+            let now = Instant::now();
+            let estimate = estimate::estimate(
+                &board,
+                seed,
+                opts.samples,
+                opts.target_error,
+                opts.max_samples.max(opts.samples),
+            );
+            let elapsed = now.elapsed();
+            let (low, high) = estimate.interval();
+            println!("  Samples:  {}", estimate.samples);
+            println!("  Estimate: {:.4e} solutions", estimate.mean);
+            println!("  95% interval: {:.4e} to {:.4e}", low, high);
+            println!(
+                "  Relative error: {:.2}%\n",
+                100.0 * estimate.relative_error()
+            );
+            if let Some(target) = opts.target_error {
+                if target < estimate.relative_error() {
+                    println!(
+                        "  Stopped at --max-samples before reaching the target error of {}%\n",
+                        100.0 * target
+                    );
+                }
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::Sample(opts) => {
+            build_thread_pool(opts.threads)?;
+            let seed = seed_or_now(opts.seed);
+            let board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            println!("\n{}", board);
+            println!("  Seed:  {}\n", seed);
+            let now = Instant::now();
+            let samples = sample::sample(&board.to_string_puzzle(), opts.n, seed, opts.probes);
+            let elapsed = now.elapsed();
+            let solutions = format_solutions(&samples, opts.compact);
+            print_solutions(samples.len(), &solutions, opts.output, elapsed)?;
+        }
+        Action::Analyze(opts) => {
+            build_thread_pool(opts.threads)?;
+            let board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let now = Instant::now();
+            let backbone = analyze::backbone(&board.to_string_puzzle());
+            let elapsed = now.elapsed();
+            match backbone {
+                Some(backbone) => {
+                    println!("{}", backbone);
+                    println!("  Empty squares: {}", backbone.empty());
+                    println!("  Fixed squares: {}, shown in brackets", backbone.fixed());
+                    println!("  Searches: {}\n", backbone.searches);
+                }
+                None => {
+                    println!("\n{}", board);
+                    println!("  Status: the puzzle has no solution\n");
+                }
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::FixUnique(opts) => {
+            build_thread_pool(opts.threads)?;
+            let board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let symmetry = fix::symmetry(&opts.symmetry);
+            let now = Instant::now();
+            let fix = fix::fix_unique(&board.to_string_puzzle(), symmetry.as_ref());
+            let elapsed = now.elapsed();
+            match fix {
+                Some(fix) => {
+                    let mut highlighted = [false; 81];
+                    for &clue in &fix.clues {
+                        highlighted[clue] = true;
+                    }
+                    let fixed = SudokuBoard::from(fix.puzzle.as_str());
+                    println!("{}", fixed.to_string_highlighted(&highlighted));
+                    println!("  Puzzle: {}\n", fix.puzzle);
+                    if fix.clues.is_empty() {
+                        println!("  Status: the puzzle already has exactly one solution\n");
+                    } else {
+                        println!(
+                            "  Added clues: {}, shown in brackets",
+                            format_clues(&fix.puzzle, &fix.clues)
+                        );
+                        println!(
+                            "  Minimum: {}\n",
+                            if fix.is_minimum {
+                                "yes, no fewer clues of this solution make the puzzle unique"
+                            } else {
+                                "not proven, the search for fewer clues stopped early"
+                            }
+                        );
+                    }
+                    println!("  Searches: {}\n", fix.searches);
+                }
+                None => {
+                    println!("\n{}", board);
+                    println!("  Status: the puzzle has no solution\n");
+                }
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::ExplainUnsolvable(opts) => {
+            build_thread_pool(opts.threads)?;
+            let board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let puzzle = board.to_string_puzzle();
+            let now = Instant::now();
+            let conflict = explain::conflict(&puzzle);
+            let elapsed = now.elapsed();
+            match conflict {
+                Some(conflict) => {
+                    let mut highlighted = [false; 81];
+                    for &clue in &conflict {
+                        highlighted[clue] = true;
+                    }
+                    println!("{}", board.to_string_highlighted(&highlighted));
+                    println!(
+                        "  Status: these {} givens have no solution together, but any {} of them do\n",
+                        conflict.len(),
+                        conflict.len() - 1
+                    );
+                    println!(
+                        "  Givens: {}, shown in brackets\n",
+                        format_clues(&puzzle, &conflict)
+                    );
+                    let repeated: Vec<_> = verify::verify(&puzzle, &puzzle)
+                        .into_iter()
+                        .filter(|violation| matches!(violation, verify::Violation::Repeated { .. }))
+                        .collect();
+                    if !repeated.is_empty() {
+                        for violation in &repeated {
+                            println!("    {}", violation);
+                        }
+                        println!();
+                    }
+                }
+                None => {
+                    println!("\n{}", board);
+                    println!(
+                        "  Status: the puzzle has a solution, so there is nothing to explain\n"
+                    );
+                }
+            }
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::CountAll(opts) if opts.shard.is_some() || opts.checkpoint.is_some() => {
+            build_thread_pool(opts.threads)?;
+            if "candidates" == opts.engine {
+                check_candidates(&opts.heuristic, false)?;
+            }
+            let (puzzle, branches) = load_branches(&opts.input, opts.shard)?;
+            let mut progress = match &opts.checkpoint {
+                Some(path) => {
+                    Checkpoint::start(path, &puzzle, opts.shard, branches.len(), opts.resume)?
+                }
+                None => Checkpoint::new(&puzzle, opts.shard, branches.len()),
+            };
+            if opts.resume {
+                println!(
+                    "  Resuming: {} of {} branches already counted, with {} solutions\n",
+                    progress.completed.len(),
+                    branches.len(),
+                    progress.count()
+                );
+            }
+            let heuristic =
+                heuristic::by_name(&opts.heuristic, heuristic_seed(&opts.heuristic, opts.seed))
+                    .expect("heuristic is one of HEURISTICS");
+            let now = Instant::now();
+            let path = opts.checkpoint.as_deref();
+            let count = checkpoint::count(&branches, &mut progress, path, |branch| {
+                match opts.engine.as_str() {
+                    "candidates" => CandidateBoard::from(branch).count_solutions(),
+                    _ => SudokuBoard::from(branch)
+                        .with_heuristic(heuristic.clone())
+                        .count_solutions(),
+                }
+            })?;
+            let elapsed = now.elapsed();
+            print_count(count);
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+            if let Some(path) = path {
+                println!("  Writing checkpoint to file: {}\n\n", path);
+            }
+        }
+        Action::CountAll(opts) if opts.exact => {
+            let puzzle = puzzle_input(&opts.input)?;
+            let board = SudokuBoard::from(puzzle.as_str());
+            println!("\n{}", board);
+            let now = Instant::now();
+            let mut counter = memo::Counter::new(&board.to_string_puzzle());
+            let count = counter.count();
+            let elapsed = now.elapsed();
+            println!("  States: {}\n", counter.states());
+            print_count(count);
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
+        Action::CountAll(opts) if "candidates" == opts.engine => {
+            build_thread_pool(opts.threads)?;
+            let is_observed = opts.trace.is_some() || opts.tree.is_some();
+            let board = load_candidates(&opts.input, &opts.heuristic, is_observed)?;
+            println!("\n{}", SudokuBoard::from(board.to_string_puzzle()));
+            let now = Instant::now();
             let count = board.count_solutions();
             let elapsed = now.elapsed();
             print_count(count);
             println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
         }
+        Action::CountAll(opts) => {
+            build_thread_pool(opts.threads)?;
+            let mut board = load_board(
+                &opts.input,
+                &opts.heuristic,
+                heuristic_seed(&opts.heuristic, opts.seed),
+            )?;
+            println!("\n{}", board);
+            let now = Instant::now();
+            let count = if opts.trace.is_some() || opts.tree.is_some() {
+                let (trace, tree) = (&opts.trace, &opts.tree);
+                find_observed(&mut board, true, false, trace, tree, opts.tree_depth)?.0
+            } else {
+                board.count_solutions()
+            };
+            let elapsed = now.elapsed();
+            print_count(count);
+            println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
+        }
         Action::FindOne(opts) => {
+            if opts.shard.is_some() {
+                return Err(IOError(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--shard splits the searches of find-all and count-all, not find-one",
+                )));
+            }
             build_thread_pool(opts.threads)?;
-            let mut board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let is_observed = opts.trace.is_some() || opts.tree.is_some();
+            let (mut board, candidates) = if opts.random {
+                (SudokuBoard::from(puzzle_input(&opts.input)?), None)
+            } else if "candidates" == opts.engine {
+                let candidates = load_candidates(&opts.input, &opts.heuristic, is_observed)?;
+                (
+                    SudokuBoard::from(candidates.to_string_puzzle()),
+                    Some(candidates),
+                )
+            } else {
+                let seed = heuristic_seed(&opts.heuristic, opts.seed);
+                (load_board(&opts.input, &opts.heuristic, seed)?, None)
+            };
+            let seed = opts.random.then(|| seed_or_now(opts.seed));
+            if let Some(seed) = seed {
+                println!("\n  Seed:  {}", seed);
+            }
             println!("\n{}", board);
             let now = Instant::now();
-            let (count, solutions) = if opts.compact {
+            let (count, solutions) = if let Some(seed) = seed {
+                let puzzle = board.to_string_puzzle();
+                let solutions: Vec<String> = sample::sample_one(&puzzle, seed, sample::PROBES)
+                    .into_iter()
+                    .collect();
+                (solutions.len(), format_solutions(&solutions, opts.compact))
+            } else if let Some(candidates) = candidates {
+                let solutions = candidates.find_up_to(1);
+                (solutions.len(), format_solutions(&solutions, opts.compact))
+            } else if is_observed {
+                let (trace, tree) = (&opts.trace, &opts.tree);
+                find_observed(
+                    &mut board,
+                    false,
+                    opts.compact,
+                    trace,
+                    tree,
+                    opts.tree_depth,
+                )?
+            } else if opts.compact {
                 board.find_one_compact()
             } else {
                 board.find_one()
@@ -263,32 +2278,74 @@ fn main() -> Result<(), SudokuError> {
                 println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
             }
         }
+        Action::FindAll(opts) if opts.shard.is_some() => {
+            build_thread_pool(opts.threads)?;
+            if "candidates" == opts.engine {
+                check_candidates(&opts.heuristic, false)?;
+            }
+            let shard = opts.shard.expect("the shard was checked above");
+            let (_, branches) = load_branches(&opts.input, Some(shard))?;
+            let heuristic =
+                heuristic::by_name(&opts.heuristic, heuristic_seed(&opts.heuristic, opts.seed))
+                    .expect("heuristic is one of HEURISTICS");
+            let now = Instant::now();
+            let (mut count, mut solutions) = (0, String::new());
+            for branch in &branches {
+                let (found, found_solutions) = match opts.engine.as_str() {
+                    "candidates" => {
+                        let found = CandidateBoard::from(branch.as_str()).find_all();
+                        (found.len(), format_solutions(&found, opts.compact))
+                    }
+                    _ => {
+                        let mut board =
+                            SudokuBoard::from(branch.as_str()).with_heuristic(heuristic.clone());
+                        if opts.compact {
+                            board.find_all_compact()
+                        } else {
+                            board.find_all()
+                        }
+                    }
+                };
+                count += found;
+                solutions += &found_solutions;
+            }
+            let elapsed = now.elapsed();
+            print_solutions(count, &solutions, opts.output, elapsed)?;
+        }
         Action::FindAll(opts) => {
             build_thread_pool(opts.threads)?;
-            let mut board = SudokuBoard::from(puzzle_input(&opts.input)?);
+            let is_observed = opts.trace.is_some() || opts.tree.is_some();
+            let (mut board, candidates) = if "candidates" == opts.engine {
+                let candidates = load_candidates(&opts.input, &opts.heuristic, is_observed)?;
+                (
+                    SudokuBoard::from(candidates.to_string_puzzle()),
+                    Some(candidates),
+                )
+            } else {
+                (
+                    load_board(
+                        &opts.input,
+                        &opts.heuristic,
+                        heuristic_seed(&opts.heuristic, opts.seed),
+                    )?,
+                    None,
+                )
+            };
             println!("\n{}", board);
             let now = Instant::now();
-            let (count, solutions) = if opts.compact {
+            let (count, solutions) = if let Some(candidates) = candidates {
+                let solutions = candidates.find_all();
+                (solutions.len(), format_solutions(&solutions, opts.compact))
+            } else if is_observed {
+                let (trace, tree) = (&opts.trace, &opts.tree);
+                find_observed(&mut board, true, opts.compact, trace, tree, opts.tree_depth)?
+            } else if opts.compact {
                 board.find_all_compact()
             } else {
                 board.find_all()
             };
             let elapsed = now.elapsed();
-            if let Some(path) = opts.output {
-                let mut file = File::create(&path)?;
-                file.write_all(solutions.as_bytes())?;
-                print_count(count);
-                println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
-                if 1 == count {
-                    println!("  Writing solution to file: {}\n\n", path);
-                } else {
-                    println!("  Writing solutions to file: {}\n\n", path);
-                }
-            } else {
-                println!("  Solutions:\n\n{}", solutions);
-                print_count(count);
-                println!("  Time:  {} seconds\n", elapsed.as_secs_f64());
-            }
+            print_solutions(count, &solutions, opts.output, elapsed)?;
         }
     }
     Ok(())
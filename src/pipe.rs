@@ -0,0 +1,92 @@
+//! A line-oriented protocol for pipelines: one puzzle per input line, one answer per output line.
+//! ```text
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  solve   975683124213945876648271539582417963437569281169832457326198745751324698894756312
+//!          unsolvable
+//!  count   35
+//!  check   unique 975683124213945876648271539582417963437569281169832457326198745751324698894756312
+//!          multiple 275936814413582976698417523... 275936814413582976698417532...
+//!          unsolvable
+//!  canon   .....1.23..2..4..534..2.678...87.5....42..8..18.945.3...9..71..217.....66581.....
+//!  grade   easy 0
+//!          unsolvable  (or multiple, for puzzles without exactly one solution)
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Blank lines are skipped. Each answer is flushed as soon as it is written.
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::{SudokuBoard, Uniqueness};
+use crate::sudoku::{canon, grade};
+use std::io;
+use std::io::{BufRead, Write};
+
+/// The names of every answer the protocol can give, as accepted by [run](fn.run.html).
+pub const PIPE_MODES: [&str; 5] = ["solve", "count", "check", "canon", "grade"];
+
+/// Answers every non-blank line of the input with one line of output, returning the number of puzzles answered.
+pub fn run<R: BufRead, W: Write>(mode: &str, input: R, output: &mut W) -> io::Result<usize> {
+    let mut count = 0;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(output, "{}", answer(mode, &line))?;
+        output.flush()?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Answers a single puzzle.
+fn answer(mode: &str, puzzle: &str) -> String {
+    let mut board = SudokuBoard::from(puzzle);
+    match mode {
+        "solve" => board
+            .find_up_to(1)
+            .pop()
+            .unwrap_or_else(|| String::from("unsolvable")),
+        "count" => board.count_solutions().to_string(),
+        "check" => match board.check_unique() {
+            Uniqueness::Unsolvable => String::from("unsolvable"),
+            Uniqueness::Unique(solution) => format!("unique {}", solution),
+            Uniqueness::Multiple(first, second) => format!("multiple {} {}", first, second),
+        },
+        "canon" => canon::canonical_form(&board),
+        "grade" => match grade::grade(&board.to_string_puzzle()) {
+            Some(grade) => format!("{} {}", grade.level, grade.guesses),
+            None => match board.check_unique() {
+                Uniqueness::Unsolvable => String::from("unsolvable"),
+                _ => String::from("multiple"),
+            },
+        },
+        _ => panic!("mode must be one of PIPE_MODES: {}", mode),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_line_per_puzzle() {
+        let input = ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.\n\n\
+                     .75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.\n\
+                     11\n";
+        let answers = |mode| {
+            let mut output = Vec::new();
+            assert_eq!(3, run(mode, input.as_bytes(), &mut output).unwrap());
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!("1\n35\n0\n", answers("count"));
+        let solved = answers("solve");
+        let solved: Vec<&str> = solved.lines().collect();
+        assert_eq!(81, solved[0].len());
+        assert_eq!("unsolvable", solved[2]);
+        let checked = answers("check");
+        let checked: Vec<&str> = checked.lines().collect();
+        assert_eq!(format!("unique {}", solved[0]), checked[0]);
+        assert!(checked[1].starts_with("multiple "));
+        assert_eq!("easy 0\nmultiple\nunsolvable\n", answers("grade"));
+    }
+}
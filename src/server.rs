@@ -0,0 +1,381 @@
+//! A local HTTP server that answers puzzle requests with JSON, so a frontend can reuse one process and one thread pool.
+//! ```text
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  POST /solve     {"puzzle": "...", "heuristic": "mrv", "seed": 7}   {"solved": true, "solution": "..."}
+//!  POST /count     {"puzzle": "..."}                                  {"count": 35}
+//!  POST /validate  {"puzzle": "..."}                                  {"status": "unique", "clues": 30, "solutions": [...]}
+//!  POST /generate  {"seed": 7}                                        {"seed": 7, "puzzle": "...", "solution": "...", "clues": 24}
+//!  POST /grade     {"puzzle": "..."}                                  {"grade": "hard", "guesses": 14, "fills": 211}
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Every answer also has the "seconds" the request took to solve.
+//!  Failed requests are answered with {"error": "..."} and a 4xx or 5xx status.
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::{SudokuBoard, Uniqueness};
+use crate::sudoku::{generate, grade, heuristic, minimize};
+use rayon::ThreadPool;
+use serde_json::{json, Value};
+use std::io;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// The largest request body the server will read, in bytes.
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+/// The settings of a running server.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The address to listen on, such as `127.0.0.1:8080`.
+    pub address: String,
+    /// The maximum number of requests being solved at once. Further requests are turned away until one finishes.
+    pub max_jobs: usize,
+    /// How long a client waits for an answer before the request fails.
+    pub timeout: Duration,
+}
+
+/// A failed request, answered with an HTTP status and an error message.
+type Failure = (u16, String);
+
+/// Listens for requests until the process is stopped, solving every request on the shared thread pool.
+pub fn serve(config: Config, pool: Arc<ThreadPool>) -> io::Result<()> {
+    let server = Server::http(&config.address).map_err(|e| io::Error::other(e.to_string()))?;
+    println!("  Listening on http://{}\n", config.address);
+    listen(server, &config, pool);
+    Ok(())
+}
+
+/// Answers every request the server receives, each on its own thread while its body is read.
+fn listen(server: Server, config: &Config, pool: Arc<ThreadPool>) {
+    let jobs = Arc::new(AtomicUsize::new(0));
+    for request in server.incoming_requests() {
+        let (pool, jobs) = (Arc::clone(&pool), Arc::clone(&jobs));
+        let (max_jobs, timeout) = (config.max_jobs, config.timeout);
+        thread::spawn(move || handle(request, &pool, jobs, max_jobs, timeout));
+    }
+}
+
+/// Reads a request, solves it on the pool, and answers it, giving up once the timeout has passed.  
+/// The job slot is only taken once the whole body has arrived, so a slow client cannot hold one.
+fn handle(
+    mut request: Request,
+    pool: &ThreadPool,
+    jobs: Arc<AtomicUsize>,
+    max_jobs: usize,
+    timeout: Duration,
+) {
+    let result = match read_body(&mut request) {
+        Ok(_) if jobs.fetch_add(1, Ordering::SeqCst) >= max_jobs => {
+            jobs.fetch_sub(1, Ordering::SeqCst);
+            Err((503, String::from("the server is busy, try again later")))
+        }
+        Ok(body) => {
+            let path = String::from(request.url());
+            let is_post = Method::Post == *request.method();
+            run(path, body, is_post, pool, jobs, timeout)
+        }
+        Err(failure) => Err(failure),
+    };
+    respond(request, result);
+}
+
+/// Solves a request body on the pool, giving back its job slot once the search has stopped.  
+/// A request that times out is cancelled, so its search stops soon instead of running on unseen,
+/// and the pool never runs more than max_jobs searches at once.
+fn run(
+    path: String,
+    body: String,
+    is_post: bool,
+    pool: &ThreadPool,
+    jobs: Arc<AtomicUsize>,
+    timeout: Duration,
+) -> Result<Value, Failure> {
+    let (tx, rx) = channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let cancel = Arc::clone(&cancel);
+        pool.spawn(move || {
+            let result = if is_post {
+                route(&path, &body, &cancel)
+            } else {
+                Err((405, String::from("only POST requests are supported")))
+            };
+            jobs.fetch_sub(1, Ordering::SeqCst);
+            let _ = tx.send(result);
+        });
+    }
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            cancel.store(true, Ordering::SeqCst);
+            Err((
+                504,
+                format!("the request timed out after {} ms", timeout.as_millis()),
+            ))
+        }
+        Err(RecvTimeoutError::Disconnected) => Err((500, String::from("the request failed"))),
+    }
+}
+
+/// Reads the body of a request as a string, failing if it is too large.
+fn read_body(request: &mut Request) -> Result<String, Failure> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, e.to_string()))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err((
+            413,
+            format!("the body must be at most {} bytes", MAX_BODY_BYTES),
+        ));
+    }
+    Ok(body)
+}
+
+/// Answers a request with a JSON body, logging its method, path, and status.
+fn respond(request: Request, result: Result<Value, Failure>) {
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, json!({ "error": error })),
+    };
+    println!("  {} {} {}", request.method(), request.url(), status);
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("the content type header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    let _ = request.respond(response);
+}
+
+/// Answers the body of a request to one of the endpoints, stopping its search early once `cancel` is set.
+fn route(path: &str, body: &str, cancel: &Arc<AtomicBool>) -> Result<Value, Failure> {
+    let body: Value = if body.trim().is_empty() {
+        json!({})
+    } else {
+        serde_json::from_str(body).map_err(|e| (400, format!("invalid JSON: {}", e)))?
+    };
+    let now = Instant::now();
+    let mut answer = match path {
+        "/solve" => solve(&body, cancel)?,
+        "/count" => json!({ "count": puzzle(&body, cancel)?.count_solutions() }),
+        "/validate" => validate(&body, cancel)?,
+        "/generate" => generate(&body, cancel)?,
+        "/grade" => grade(&body, cancel)?,
+        _ => return Err((404, format!("unknown endpoint: {}", path))),
+    };
+    answer["seconds"] = json!(now.elapsed().as_secs_f64());
+    Ok(answer)
+}
+
+fn solve(body: &Value, cancel: &Arc<AtomicBool>) -> Result<Value, Failure> {
+    let name = body
+        .get("heuristic")
+        .and_then(Value::as_str)
+        .unwrap_or("mrv");
+    let heuristic = heuristic::by_name(name, seed(body)?)
+        .ok_or_else(|| (400, format!("unknown heuristic: {}", name)))?;
+    let solution = puzzle(body, cancel)?
+        .with_heuristic(heuristic)
+        .find_up_to(1)
+        .pop();
+    Ok(json!({ "solved": solution.is_some(), "solution": solution }))
+}
+
+fn validate(body: &Value, cancel: &Arc<AtomicBool>) -> Result<Value, Failure> {
+    let mut board = puzzle(body, cancel)?;
+    let clues = minimize::clues(&board.to_string_puzzle()).len();
+    let (status, solutions) = match board.check_unique() {
+        Uniqueness::Unsolvable => ("unsolvable", vec![]),
+        Uniqueness::Unique(solution) => ("unique", vec![solution]),
+        Uniqueness::Multiple(first, second) => ("multiple", vec![first, second]),
+    };
+    Ok(json!({ "status": status, "clues": clues, "solutions": solutions }))
+}
+
+fn generate(body: &Value, cancel: &Arc<AtomicBool>) -> Result<Value, Failure> {
+    let seed = seed(body)?;
+    let (puzzle, solution) = generate::generate_cancellable(seed, cancel)
+        .ok_or_else(|| (503, String::from("the request was cancelled")))?;
+    let clues = minimize::clues(&puzzle).len();
+    Ok(json!({ "seed": seed, "puzzle": puzzle, "solution": solution, "clues": clues }))
+}
+
+fn grade(body: &Value, cancel: &Arc<AtomicBool>) -> Result<Value, Failure> {
+    let puzzle = puzzle(body, cancel)?.to_string_puzzle();
+    let grade = grade::grade_cancellable(&puzzle, cancel).ok_or_else(|| {
+        (
+            422,
+            String::from("only a puzzle with exactly one solution can be graded"),
+        )
+    })?;
+    Ok(json!({ "grade": grade.level, "guesses": grade.guesses, "fills": grade.fills }))
+}
+
+/// Reads the required `puzzle` field of a request as a board whose searches stop once `cancel` is set.
+fn puzzle(body: &Value, cancel: &Arc<AtomicBool>) -> Result<SudokuBoard, Failure> {
+    match body.get("puzzle").and_then(Value::as_str) {
+        Some(puzzle) => Ok(SudokuBoard::from(puzzle).with_cancel(Arc::clone(cancel))),
+        None => Err((400, String::from("the body must have a \"puzzle\" string"))),
+    }
+}
+
+/// Reads the optional `seed` field of a request, defaulting to the current time.
+fn seed(body: &Value) -> Result<u64, Failure> {
+    match body.get("seed") {
+        None | Some(Value::Null) => Ok(crate::seed_or_now(None)),
+        Some(seed) => seed
+            .as_u64()
+            .ok_or_else(|| (400, String::from("\"seed\" must be a non-negative integer"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn endpoints() {
+        let body = json!({ "puzzle": PUZZLE }).to_string();
+        let cancel = Arc::default();
+        let solved = route("/solve", &body, &cancel).unwrap();
+        assert_eq!(json!(true), solved["solved"]);
+        assert_eq!(json!(1), route("/count", &body, &cancel).unwrap()["count"]);
+        let validated = route("/validate", &body, &cancel).unwrap();
+        assert_eq!(json!("unique"), validated["status"]);
+        assert_eq!(solved["solution"], validated["solutions"][0]);
+        assert_eq!(
+            json!("easy"),
+            route("/grade", &body, &cancel).unwrap()["grade"]
+        );
+        let generated = route("/generate", "{\"seed\": 5}", &cancel).unwrap();
+        assert_eq!(json!(5), generated["seed"]);
+        let puzzle = generated["puzzle"].as_str().unwrap();
+        assert!(minimize::is_unique(puzzle));
+    }
+
+    #[test]
+    fn failures() {
+        let status = |path, body| route(path, body, &Arc::default()).unwrap_err().0;
+        assert_eq!(404, status("/unknown", "{}"));
+        assert_eq!(400, status("/solve", "{"));
+        assert_eq!(400, status("/count", "{}"));
+        assert_eq!(400, status("/generate", "{\"seed\": -1}"));
+        let body = json!({ "puzzle": PUZZLE, "heuristic": "unknown" }).to_string();
+        assert_eq!(400, status("/solve", &body));
+        let multiple = json!({ "puzzle": "1" }).to_string();
+        assert_eq!(422, status("/grade", &multiple));
+    }
+
+    #[test]
+    fn timeouts_cancel_the_search() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let jobs = Arc::new(AtomicUsize::new(1));
+        let body = json!({ "puzzle": "" }).to_string();
+        let timeout = Duration::from_millis(50);
+        let post = |path: &str, body: &str, timeout| {
+            let (path, body) = (String::from(path), String::from(body));
+            run(path, body, true, &pool, Arc::clone(&jobs), timeout)
+        };
+        assert_eq!(504, post("/count", &body, timeout).unwrap_err().0);
+        wait_for_no_jobs(&jobs);
+
+        // The only thread in the pool is free again once the cancelled search winds down.
+        jobs.fetch_add(1, Ordering::SeqCst);
+        let body = json!({ "puzzle": PUZZLE }).to_string();
+        let counted = post("/count", &body, Duration::from_secs(30)).unwrap();
+        assert_eq!(json!(1), counted["count"]);
+        assert_eq!(0, jobs.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn timed_out_grades_hold_their_slot_until_cancelled() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        // Keep the only thread busy so the grade is still waiting when it times out.
+        let (unblock, blocked) = channel::<()>();
+        pool.spawn(move || {
+            let _ = blocked.recv();
+        });
+        let jobs = Arc::new(AtomicUsize::new(1));
+        let body = json!({ "puzzle": PUZZLE }).to_string();
+        let timeout = Duration::from_millis(20);
+        let result = run(
+            String::from("/grade"),
+            body,
+            true,
+            &pool,
+            Arc::clone(&jobs),
+            timeout,
+        );
+        assert_eq!(504, result.unwrap_err().0);
+        thread::sleep(timeout);
+        assert_eq!(1, jobs.load(Ordering::SeqCst));
+        unblock.send(()).unwrap();
+        wait_for_no_jobs(&jobs);
+    }
+
+    #[test]
+    fn slow_bodies_do_not_hold_a_slot() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        let config = Config {
+            address: address.to_string(),
+            max_jobs: 1,
+            timeout: Duration::from_secs(30),
+        };
+        let pool = Arc::new(rayon::ThreadPoolBuilder::new().build().unwrap());
+        thread::spawn(move || listen(server, &config, pool));
+
+        // Headers that promise a body which never arrives.
+        let mut slow = TcpStream::connect(address).unwrap();
+        write!(
+            slow,
+            "POST /count HTTP/1.1\r\nHost: x\r\nContent-Length: 100\r\n\r\n{{"
+        )
+        .unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        let body = json!({ "puzzle": PUZZLE }).to_string();
+        let mut client = TcpStream::connect(address).unwrap();
+        write!(
+            client,
+            "POST /count HTTP/1.1\r\nHost: x\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    }
+
+    /// Waits for every job to give back its slot, failing if that takes more than a few seconds.
+    fn wait_for_no_jobs(jobs: &AtomicUsize) {
+        let start = Instant::now();
+        while 0 != jobs.load(Ordering::SeqCst) {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "a job kept its slot"
+            );
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
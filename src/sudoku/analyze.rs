@@ -0,0 +1,151 @@
+//! Finds the backbone of a puzzle: the empty squares that hold the same value in every solution, and the
+//! set of values every other square takes across the solutions, without listing the solutions.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Every value a square takes in some solution is one of its options on the puzzle, so only those are
+//!  tried. Every solution found marks each of its 81 values as taken, so only the options no solution
+//!  has shown yet are searched: a search with the option filled in either finds a solution, which marks
+//!  many more values at once, or proves that no solution has that value there.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::board::{SudokuBoard, SudokuSquare};
+use std::fmt;
+
+/// The values every square takes across the solutions of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backbone {
+    /// The puzzle string of 81 characters, with `.` for each empty square.
+    pub puzzle: String,
+    /// The values each square takes in some solution, where bit `value - 1` stands for `value`.
+    pub values: [u16; 81],
+    /// The number of searches it took to decide every option.
+    pub searches: usize,
+}
+
+impl Backbone {
+    /// Returns true if the square at an index `(row * 9 + col)` is empty but has the same value in every solution.
+    pub fn is_fixed(&self, index: usize) -> bool {
+        b'.' == self.puzzle.as_bytes()[index] && 1 == self.values[index].count_ones()
+    }
+
+    /// Returns the number of empty squares.
+    pub fn empty(&self) -> usize {
+        self.puzzle.matches('.').count()
+    }
+
+    /// Returns the number of empty squares that have the same value in every solution.
+    pub fn fixed(&self) -> usize {
+        (0..81).filter(|&index| self.is_fixed(index)).count()
+    }
+
+    /// Returns one of the three lines of a square's block: the values `3 * line + 1` to `3 * line + 3`.
+    fn format_line(&self, index: usize, line: usize) -> String {
+        let values = self.values[index];
+        let given = self.puzzle.as_bytes()[index];
+        if b'.' != given || self.is_fixed(index) {
+            let value = match given {
+                b'.' => (values.trailing_zeros() + 1).to_string(),
+                given => (given as char).to_string(),
+            };
+            return match (line, b'.' == given) {
+                (1, true) => format!("[{}]", value),
+                (1, false) => format!(" {} ", value),
+                _ => String::from("   "),
+            };
+        }
+        (3 * line + 1..=3 * line + 3)
+            .map(|value| match values & 1 << (value - 1) {
+                0 => ' ',
+                _ => std::char::from_digit(value as u32, 10).unwrap_or(' '),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Backbone {
+    /// Draws the board with the set of values of each square in its own 3 by 3 block of digits.
+    /// Givens are drawn alone in the middle of their block, and fixed squares in brackets.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\n  ╔═══════════╦═══════════╦═══════════╗")?;
+        for row in 0..9 {
+            for line in 0..3 {
+                write!(f, "  ║")?;
+                for col in 0..9 {
+                    let separator = if 2 == col % 3 { '║' } else { '│' };
+                    write!(f, "{}{}", self.format_line(9 * row + col, line), separator)?;
+                }
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{}",
+                match row {
+                    2 | 5 => "  ╠═══════════╬═══════════╬═══════════╣\n",
+                    8 => "  ╚═══════════╩═══════════╩═══════════╝\n",
+                    _ => "  ║───┼───┼───║───┼───┼───║───┼───┼───║\n",
+                }
+            )?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Finds the values every square takes across the solutions of a puzzle string, or `None` if it has no solution.
+pub fn backbone(puzzle: &str) -> Option<Backbone> {
+    let board = SudokuBoard::from(puzzle);
+    let puzzle = board.to_string_puzzle();
+    let mut backbone = Backbone {
+        puzzle: puzzle.clone(),
+        values: [0; 81],
+        searches: 1,
+    };
+    mark(
+        &mut backbone.values,
+        &SudokuBoard::from(puzzle.as_str()).find_up_to(1).pop()?,
+    );
+    for index in (0..81).filter(|&index| b'.' == puzzle.as_bytes()[index]) {
+        let options = board.options(&SudokuSquare::at(index / 9, index % 9));
+        for value in (1..=9).filter(|&value| 0 == options & as_bit(value)) {
+            if 0 != backbone.values[index] & 1 << (value - 1) {
+                continue;
+            }
+            let mut child = puzzle.clone();
+            child.replace_range(index..=index, &value.to_string());
+            backbone.searches += 1;
+            if let Some(solution) = SudokuBoard::from(child).find_up_to(1).pop() {
+                mark(&mut backbone.values, &solution);
+            }
+        }
+    }
+    Some(backbone)
+}
+
+/// Marks every value of a solution of 81 digits as taken by its square.
+fn mark(values: &mut [u16; 81], solution: &str) {
+    for (values, digit) in values.iter_mut().zip(solution.bytes()) {
+        *values |= 1 << (digit - b'1');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_every_solution() {
+        let puzzle =
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+        let backbone = backbone(puzzle).unwrap();
+        let mut values = [0; 81];
+        for solution in SudokuBoard::from(puzzle).find_up_to(100) {
+            mark(&mut values, &solution);
+        }
+        assert_eq!(values, backbone.values);
+        assert!(0 < backbone.fixed() && backbone.fixed() < backbone.empty());
+        assert_eq!(39, backbone.to_string().matches('\n').count());
+        assert_eq!(backbone.fixed(), backbone.to_string().matches('[').count());
+        assert_eq!(None, super::backbone("11"));
+    }
+}
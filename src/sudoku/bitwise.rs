@@ -21,6 +21,7 @@ const FOUR_SET_BITS: u64 = 0b1111;
 const NINE_SET_BITS: u64 = 0b111111111;
 
 /// Clear a value by bitwise & with one of these.
+#[allow(clippy::unusual_byte_groupings)]
 const CLEAR: [u64; 9] = [
     0b1_111111111_111111111_111111111_0000_1111_1111_1111_1111_1111_1111_1111_1111,
     0b1_111111111_111111111_111111111_1111_0000_1111_1111_1111_1111_1111_1111_1111,
@@ -177,6 +178,7 @@ pub const fn zero_out_square(data: u64, col: usize) -> u64 {
 }
 
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod tests {
     use super::*;
 
@@ -29,13 +29,16 @@
 
 use super::data::SudokuData;
 use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::heuristic::{Heuristic, MinimumRemainingValues};
+use crate::sudoku::random::{mix, SplitMix64};
 use ansi_escapes::{CursorHide, CursorRestorePosition, CursorSavePosition, CursorShow};
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 use std::borrow::Borrow;
-use std::iter::repeat;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::{fmt, iter, thread};
+use std::{fmt, thread};
 
 /// The number of bytes in the string representation of the board.
 const BOARD_STRING_LENGTH: usize = 1682;
@@ -49,38 +52,99 @@ pub struct SudokuBoard {
     board: [SudokuData; 9],
     fillable_squares: Vec<SudokuSquare>,
     is_solvable: bool,
+    heuristic: Arc<dyn Heuristic>,
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 /// A sudoku square represents a value that is in a particular `(row, col, box)`.  
 /// A square's location is fully determined by `(row, col)` alone,  
 /// but the box is important information for validation.  
 #[derive(Clone, Debug)]
-struct SudokuSquare(usize, usize, usize);
+pub struct SudokuSquare(usize, usize, usize);
 
 impl SudokuSquare {
     /// Creates a new `SudokuSquare`.
     pub fn new(row: usize, col: usize, bx: usize) -> Self {
         SudokuSquare(row, col, bx)
     }
+
+    /// Creates a new `SudokuSquare` at a given `(row, col)`.
+    pub fn at(row: usize, col: usize) -> Self {
+        SudokuSquare(row, col, box_index(row, col))
+    }
+
+    /// Returns the row of this square.
+    pub fn row(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the column of this square.
+    pub fn col(&self) -> usize {
+        self.1
+    }
+
+    /// Returns the box of this square.
+    pub fn bx(&self) -> usize {
+        self.2
+    }
+}
+
+/// Whether a puzzle has no solution, exactly one solution, or more than one solution.  
+/// Solutions are compact strings of 81 contiguous digits `(1..=9)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Uniqueness {
+    Unsolvable,
+    Unique(String),
+    /// Two distinct solutions that witness the puzzle is not unique.
+    Multiple(String, String),
+}
+
+/// An event produced by the sequential search as it fills and clears squares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchEvent {
+    /// The square at `(row, col)` was chosen to branch on, with `options` values to try.
+    Branch {
+        row: usize,
+        col: usize,
+        options: u32,
+        depth: usize,
+    },
+    /// A value was written into the square at `(row, col)`, `depth` squares into the search.
+    Fill {
+        row: usize,
+        col: usize,
+        value: usize,
+        depth: usize,
+    },
+    /// The square at `(row, col)` was cleared after every option was tried.
+    Clear {
+        row: usize,
+        col: usize,
+        depth: usize,
+    },
+    /// The board was completely filled, `depth` squares into the search.
+    Solution { depth: usize },
+    /// Some empty square has no options left, `depth` squares into the search.
+    DeadEnd { depth: usize },
 }
 
 /// Given a row and a column, returns which box that square is in.
 fn box_index(row: usize, col: usize) -> usize {
     match row {
-        0 | 1 | 2 => match col {
-            0 | 1 | 2 => 0,
-            3 | 4 | 5 => 1,
-            _________ => 2,
+        0..=2 => match col {
+            0..=2 => 0,
+            3..=5 => 1,
+            _ => 2,
         },
-        3 | 4 | 5 => match col {
-            0 | 1 | 2 => 3,
-            3 | 4 | 5 => 4,
-            _________ => 5,
+        3..=5 => match col {
+            0..=2 => 3,
+            3..=5 => 4,
+            _ => 5,
         },
-        _________ => match col {
-            0 | 1 | 2 => 6,
-            3 | 4 | 5 => 7,
-            _________ => 8,
+        _ => match col {
+            0..=2 => 6,
+            3..=5 => 7,
+            _ => 8,
         },
     }
 }
@@ -155,8 +219,13 @@ impl SudokuBoard {
         self.board[row].clear_square(col);
     }
 
+    /// Returns false if two givens share a row, column, or box, so the board has no solution.
+    pub fn is_solvable(&self) -> bool {
+        self.is_solvable
+    }
+
     /// Retrieves the value at a given square.
-    fn value_at(&self, &SudokuSquare(row, col, _): &SudokuSquare) -> u64 {
+    pub fn value_at(&self, &SudokuSquare(row, col, _): &SudokuSquare) -> u64 {
         self.board[row].value_at(col)
     }
 
@@ -164,7 +233,7 @@ impl SudokuBoard {
     /// For example, if `0b101010101` is returned, this means that  
     /// `{ 1, 3, 5, 7, 9 }` are already present in the row/col/box and that  
     /// `{ 2, 4, 6, 8 }` are available options.  
-    fn options(&self, &SudokuSquare(row, col, bx): &SudokuSquare) -> u64 {
+    pub fn options(&self, &SudokuSquare(row, col, bx): &SudokuSquare) -> u64 {
         self.board[row].values_in_row()
             | self.board[col].values_in_col()
             | self.board[bx].values_in_box()
@@ -173,24 +242,26 @@ impl SudokuBoard {
     /// Returns an iterator over every value that is an available option for this square.  
     /// For example, if `{ 1, 3, 5, 7, 9 }` are already present in this square's row/col/box,  
     /// then this will return an iterator over `{ 2 } -> { 4 } -> { 6 } -> { 8 } -> None`  
+    /// The order of the values is decided by the board's heuristic.  
     fn options_iter(&self, square: &SudokuSquare) -> impl Iterator<Item = usize> {
-        let mut start_value = 1;
         let options = self.options(square);
-        iter::from_fn(move || {
-            for value in start_value..=9 {
-                if 0 == options & as_bit(value) {
-                    start_value = value + 1;
-                    return Some(value);
-                }
-            }
-            return None;
-        })
+        let mut values = [0; 9];
+        let mut len = 0;
+        for value in (1..=9).filter(|&value| 0 == options & as_bit(value)) {
+            values[len] = value;
+            len += 1;
+        }
+        if 1 < len {
+            self.heuristic
+                .order_values(self, square, &mut values[..len]);
+        }
+        IntoIterator::into_iter(values).take(len)
     }
 
     /// Returns the count of available options for this square.  
     /// For example, if { 1, 3, 5, 7, 9 } are already present in this square's row/col/box,  
     /// then this will return 4, because { 2, 4, 6, 8 } are all available options.  
-    fn count_options(&self, square: &SudokuSquare) -> u32 {
+    pub fn count_options(&self, square: &SudokuSquare) -> u32 {
         9 - self.options(square).count_ones()
     }
 
@@ -250,6 +321,14 @@ impl SudokuBoard {
         count
     }
 
+    /// Redraws the board in place of the previous frame.
+    fn print_frame(&self, count: usize) {
+        print!(
+            "{}{}\n{}\n  Solutions: {}",
+            CursorRestorePosition, CursorSavePosition, self, count
+        );
+    }
+
     /// Watch the board find solutions in the terminal.
     pub fn watch_find_all(&mut self, millis_per_frame: u64) {
         println!("{}", CursorHide);
@@ -273,16 +352,10 @@ impl SudokuBoard {
         thread::sleep(Duration::from_millis(millis_per_frame));
         if self.fillable_squares.is_empty() {
             *count += 1;
-            print!(
-                "{}{}\n{}\n  Solutions: {}",
-                CursorRestorePosition, CursorSavePosition, self, count
-            );
+            self.print_frame(*count);
             return;
         }
-        print!(
-            "{}{}\n{}\n  Solutions: {}",
-            CursorRestorePosition, CursorSavePosition, self, count
-        );
+        self.print_frame(*count);
         if let Some(square) = self.next_fillable_square() {
             for value in self.options_iter(&square) {
                 self.fill(&square, value);
@@ -291,10 +364,7 @@ impl SudokuBoard {
             self.clear(&square);
             self.fillable_squares.push(square);
             thread::sleep(Duration::from_millis(millis_per_frame));
-            print!(
-                "{}{}\n{}\n  Solutions: {}",
-                CursorRestorePosition, CursorSavePosition, self, count
-            );
+            self.print_frame(*count);
         }
     }
 
@@ -324,16 +394,10 @@ impl SudokuBoard {
         thread::sleep(Duration::from_millis(millis_per_frame));
         if self.fillable_squares.is_empty() {
             *count += 1;
-            print!(
-                "{}{}\n{}\n  Solutions: {}",
-                CursorRestorePosition, CursorSavePosition, self, count
-            );
+            self.print_frame(*count);
             return;
         }
-        print!(
-            "{}{}\n{}\n  Solutions: {}",
-            CursorRestorePosition, CursorSavePosition, self, count
-        );
+        self.print_frame(*count);
         if let Some(square) = self.next_fillable_square() {
             for value in self.options_iter(&square) {
                 self.fill(&square, value);
@@ -342,10 +406,105 @@ impl SudokuBoard {
             self.clear(&square);
             self.fillable_squares.push(square);
             thread::sleep(Duration::from_millis(millis_per_frame));
-            print!(
-                "{}{}\n{}\n  Solutions: {}",
-                CursorRestorePosition, CursorSavePosition, self, count
+            self.print_frame(*count);
+        }
+    }
+
+    /// Watch a recorded sequence of search events play out in the terminal.
+    pub fn watch_replay(&mut self, events: &[SearchEvent], millis_per_frame: u64) {
+        println!("{}", CursorHide);
+        let mut count = 0;
+        print!("{}", CursorSavePosition);
+        self.print_frame(count);
+        for event in events {
+            if let SearchEvent::Branch { .. } | SearchEvent::DeadEnd { .. } = event {
+                continue;
+            }
+            thread::sleep(Duration::from_millis(millis_per_frame));
+            match *event {
+                SearchEvent::Fill {
+                    row, col, value, ..
+                } => self.fill(&SudokuSquare::new(row, col, box_index(row, col)), value),
+                SearchEvent::Clear { row, col, .. } => {
+                    self.clear(&SudokuSquare::new(row, col, box_index(row, col)))
+                }
+                SearchEvent::Solution { .. } => count += 1,
+                SearchEvent::Branch { .. } | SearchEvent::DeadEnd { .. } => {}
+            }
+            self.print_frame(count);
+        }
+        println!("{}", CursorShow);
+    }
+
+    /// Search sequentially, reporting every fill, clear, and solution to an observer.  
+    /// Returns the number of solutions found, stopping after the first if `find_all` is false.
+    pub fn search_observed<F>(&mut self, find_all: bool, observer: &mut F) -> usize
+    where
+        F: FnMut(&SudokuBoard, SearchEvent),
+    {
+        if !self.is_solvable {
+            return 0;
+        }
+        let mut count = 0;
+        self.analyze_fillable_squares();
+        self.search_observed_seq(find_all, 0, &mut count, observer);
+        count
+    }
+
+    /// Search sequentially, reporting every fill, clear, and solution to an observer.
+    fn search_observed_seq<F>(
+        &mut self,
+        find_all: bool,
+        depth: usize,
+        count: &mut usize,
+        observer: &mut F,
+    ) where
+        F: FnMut(&SudokuBoard, SearchEvent),
+    {
+        if self.fillable_squares.is_empty() {
+            *count += 1;
+            observer(self, SearchEvent::Solution { depth });
+            return;
+        }
+        if let Some(square) = self.next_fillable_square() {
+            let SudokuSquare(row, col, _) = square;
+            observer(
+                self,
+                SearchEvent::Branch {
+                    row,
+                    col,
+                    options: self.count_options(&square),
+                    depth,
+                },
+            );
+            for value in self.options_iter(&square) {
+                if !find_all && 0 < *count {
+                    break;
+                }
+                self.fill(&square, value);
+                observer(
+                    self,
+                    SearchEvent::Fill {
+                        row,
+                        col,
+                        value,
+                        depth: depth + 1,
+                    },
+                );
+                self.search_observed_seq(find_all, depth + 1, count, observer);
+            }
+            self.clear(&square);
+            observer(
+                self,
+                SearchEvent::Clear {
+                    row,
+                    col,
+                    depth: depth + 1,
+                },
             );
+            self.fillable_squares.push(square);
+        } else {
+            observer(self, SearchEvent::DeadEnd { depth });
         }
     }
 
@@ -505,7 +664,7 @@ impl SudokuBoard {
         (count, solutions)
     }
 
-    /// Find all solutions in parallel and return each solved board in a String sequentially.
+    /// Find one solution in parallel, keeping the first solution in the order the values were tried.
     fn find_one_par(&mut self) -> (usize, String) {
         if self.fillable_squares.is_empty() {
             return (1, self.to_string());
@@ -516,22 +675,24 @@ impl SudokuBoard {
             let num_options = self.count_options(&square);
             let (tx, rx) = channel();
             self.options_iter(&square)
+                .enumerate()
                 .par_bridge()
-                .try_for_each_with(tx, |tx, value| {
+                .try_for_each_with(tx, |tx, (index, value)| {
                     let mut board = self.clone();
                     board.fill(&square, value);
                     if num_options > 1 {
-                        tx.send(board.find_one_par())
+                        tx.send((index, board.find_one_par()))
                     } else {
-                        tx.send(board.find_one_seq())
+                        tx.send((index, board.find_one_seq()))
                     }
                 })
                 .expect("Failed to invoke on multiple threads.");
-            for _ in 0..num_options {
-                if 0 < count {
-                    break;
-                }
-                let (new_count, new_sols) = rx.recv().unwrap();
+            let mut results: Vec<_> = rx.iter().take(num_options as usize).collect();
+            results.sort_unstable_by_key(|&(index, _)| index);
+            if let Some((_, (new_count, new_sols))) = results
+                .into_iter()
+                .find(|(_, (new_count, _))| 0 < *new_count)
+            {
                 count += new_count;
                 solutions += &new_sols;
             }
@@ -573,7 +734,7 @@ impl SudokuBoard {
         (count, solutions)
     }
 
-    /// Find all solutions in parallel and return each solved board in a String sequentially.
+    /// Find one solution in parallel, keeping the first solution in the order the values were tried.
     fn find_one_compact_par(&mut self) -> (usize, String) {
         if self.fillable_squares.is_empty() {
             return (1, self.to_string_compact());
@@ -584,22 +745,24 @@ impl SudokuBoard {
             let num_options = self.count_options(&square);
             let (tx, rx) = channel();
             self.options_iter(&square)
+                .enumerate()
                 .par_bridge()
-                .try_for_each_with(tx, |tx, value| {
+                .try_for_each_with(tx, |tx, (index, value)| {
                     let mut board = self.clone();
                     board.fill(&square, value);
                     if num_options > 1 {
-                        tx.send(board.find_one_compact_par())
+                        tx.send((index, board.find_one_compact_par()))
                     } else {
-                        tx.send(board.find_one_compact_seq())
+                        tx.send((index, board.find_one_compact_seq()))
                     }
                 })
                 .expect("Failed to invoke on multiple threads.");
-            for _ in 0..num_options {
-                if 0 < count {
-                    break;
-                }
-                let (new_count, new_sols) = rx.recv().unwrap();
+            let mut results: Vec<_> = rx.iter().take(num_options as usize).collect();
+            results.sort_unstable_by_key(|&(index, _)| index);
+            if let Some((_, (new_count, new_sols))) = results
+                .into_iter()
+                .find(|(_, (new_count, _))| 0 < *new_count)
+            {
                 count += new_count;
                 solutions += &new_sols;
             }
@@ -609,32 +772,161 @@ impl SudokuBoard {
         (count, solutions)
     }
 
-    /// Returns the next best square in which to try a value, removing it from the vector.  
-    /// That is the first encountered square if only 1 option.  
-    /// Or else any square that is tied for the least number of options.  
-    fn next_fillable_square(&mut self) -> Option<SudokuSquare> {
-        let mut index = 0;
-        let mut min_options = self.count_options(&self.fillable_squares[index]);
-        for i in (1..self.fillable_squares.len()).rev() {
-            match min_options {
-                0 => return None,
-                1 => return Some(self.fillable_squares.swap_remove(index)),
-                _ => {
-                    let curr_options = self.count_options(&self.fillable_squares[i]);
-                    if curr_options < min_options {
-                        min_options = curr_options;
-                        index = i;
+    /// Checks whether this board has zero, one, or more than one solution,  
+    /// stopping the parallel search as soon as a second solution is found.
+    pub fn check_unique(&mut self) -> Uniqueness {
+        let mut solutions = self.find_up_to(2).into_iter();
+        match (solutions.next(), solutions.next()) {
+            (None, _) => Uniqueness::Unsolvable,
+            (Some(solution), None) => Uniqueness::Unique(solution),
+            (Some(first), Some(second)) => Uniqueness::Multiple(first, second),
+        }
+    }
+
+    /// Find at most `limit` solutions in parallel, stopping every thread once the limit is reached.  
+    /// Returns each solution as a compact string of 81 contiguous digits `(1..=9)`.
+    pub fn find_up_to(&mut self, limit: usize) -> Vec<String> {
+        if !self.is_solvable || 0 == limit {
+            return Vec::new();
+        }
+        self.analyze_fillable_squares();
+        self.find_up_to_par(limit, &AtomicUsize::new(0))
+    }
+
+    /// Find solutions sequentially until `found` reaches the limit.
+    fn find_up_to_seq(&mut self, limit: usize, found: &AtomicUsize, solutions: &mut Vec<String>) {
+        if limit <= found.load(Ordering::Relaxed) {
+            return;
+        }
+        if self.fillable_squares.is_empty() {
+            if found.fetch_add(1, Ordering::Relaxed) < limit {
+                solutions.push(self.to_string_puzzle());
+            }
+            return;
+        }
+        if let Some(square) = self.next_fillable_square() {
+            for value in self.options_iter(&square) {
+                self.fill(&square, value);
+                self.find_up_to_seq(limit, found, solutions);
+            }
+            self.clear(&square);
+            self.fillable_squares.push(square);
+        }
+    }
+
+    /// Find solutions in parallel until `found` reaches the limit.
+    fn find_up_to_par(&mut self, limit: usize, found: &AtomicUsize) -> Vec<String> {
+        let mut solutions = Vec::new();
+        if limit <= found.load(Ordering::Relaxed) {
+            return solutions;
+        }
+        if self.fillable_squares.is_empty() {
+            if found.fetch_add(1, Ordering::Relaxed) < limit {
+                solutions.push(self.to_string_puzzle());
+            }
+            return solutions;
+        }
+        if let Some(square) = self.next_fillable_square() {
+            let num_options = self.count_options(&square);
+            let (tx, rx) = channel();
+            self.options_iter(&square)
+                .par_bridge()
+                .try_for_each_with(tx, |tx, value| {
+                    let mut board = self.clone();
+                    board.fill(&square, value);
+                    if num_options > 1 {
+                        tx.send(board.find_up_to_par(limit, found))
+                    } else {
+                        let mut solutions = Vec::new();
+                        board.find_up_to_seq(limit, found, &mut solutions);
+                        tx.send(solutions)
                     }
-                }
+                })
+                .expect("Failed to invoke on multiple threads.");
+            for _ in 0..num_options {
+                solutions.append(&mut rx.recv().unwrap());
             }
+            self.clear(&square);
+            self.fillable_squares.push(square);
+        }
+        solutions
+    }
+
+    /// Walks one random path from the top of the search tree, filling the square chosen by the heuristic
+    /// with one of its options picked uniformly by `random` until the board is solved or stuck.  
+    /// Returns the product of the number of options along the path if it ends in a solution, or 0 if
+    /// it ends at a square without options. Its mean over many walks is the number of solutions.
+    pub fn probe(&self, random: &mut SplitMix64) -> f64 {
+        if !self.is_solvable {
+            return 0.0;
+        }
+        let mut board = self.clone();
+        board.analyze_fillable_squares();
+        let mut weight = 1.0;
+        while !board.fillable_squares.is_empty() {
+            let square = match board.next_fillable_square() {
+                Some(square) => square,
+                None => return 0.0,
+            };
+            let mut values = [0; 9];
+            let mut len = 0;
+            for value in board.options_iter(&square) {
+                values[len] = value;
+                len += 1;
+            }
+            if 0 == len {
+                return 0.0;
+            }
+            weight *= len as f64;
+            board.fill(&square, values[random.below(len)]);
+        }
+        weight
+    }
+
+    /// Returns the square the heuristic chooses to try a value in next, removing it from the vector.  
+    /// Returns `None` if the heuristic found a square with no options left, or if the search was cancelled.  
+    fn next_fillable_square(&mut self) -> Option<SudokuSquare> {
+        if self.is_cancelled() {
+            return None;
         }
+        let index = self.heuristic.select_square(self, &self.fillable_squares)?;
         Some(self.fillable_squares.swap_remove(index))
     }
 
+    /// Replaces the heuristic that chooses which square to fill next and the order in which to try values.
+    pub fn with_heuristic(mut self, heuristic: Arc<dyn Heuristic>) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Shares a flag that ends every search on this board early once it is set.  
+    /// A cancelled search returns whatever it found so far, which is not a complete answer.
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Returns whether the flag shared with `with_cancel` has been set.
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Returns a hash of the values currently on the board.
+    pub fn state_hash(&self) -> u64 {
+        let mut values = [0; 9];
+        for (value, data) in values.iter_mut().zip(self.board.iter()) {
+            *value = data.as_u64();
+        }
+        mix(&values)
+    }
+
     /// Returns a string representation of the board.
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
         let mut string = String::with_capacity(BOARD_STRING_LENGTH);
-        string.push_str("\n");
+        string.push('\n');
         string.push_str("  ╔═══════════╦═══════════╦═══════════╗\n");
         string.push_str(&self.board[0].to_string());
         string.push_str("  ║───┼───┼───║───┼───┼───║───┼───┼───║\n");
@@ -654,17 +946,45 @@ impl SudokuBoard {
         string.push_str("  ║───┼───┼───║───┼───┼───║───┼───┼───║\n");
         string.push_str(&self.board[8].to_string());
         string.push_str("  ╚═══════════╩═══════════╩═══════════╝\n");
-        string.push_str("\n");
+        string.push('\n');
         string
     }
 
+    /// Formats the board like [to_string](#method.to_string), with brackets around every square
+    /// whose index from 0 to 80 is set in `highlighted`.
+    pub fn to_string_highlighted(&self, highlighted: &[bool; 81]) -> String {
+        let mut string = String::with_capacity(BOARD_STRING_LENGTH);
+        string.push('\n');
+        string.push_str("  ╔═══════════╦═══════════╦═══════════╗\n");
+        for row in 0..9 {
+            string.push_str(
+                &self.board[row].to_string_highlighted(&highlighted[9 * row..9 * row + 9]),
+            );
+            string.push_str(match row {
+                2 | 5 => "  ╠═══════════╬═══════════╬═══════════╣\n",
+                8 => "  ╚═══════════╩═══════════╩═══════════╝\n",
+                _ => "  ║───┼───┼───║───┼───┼───║───┼───┼───║\n",
+            });
+        }
+        string.push('\n');
+        string
+    }
+
+    /// Returns the board as a puzzle string of 81 characters, with `.` for each empty square.
+    pub fn to_string_puzzle(&self) -> String {
+        self.board
+            .iter()
+            .map(SudokuData::to_string_puzzle)
+            .collect()
+    }
+
     /// Returns a compact string representation of the board: 81 contiguous digits `(1..=9)`
     pub fn to_string_compact(&self) -> String {
         let mut string = String::with_capacity(COMPACT_BOARD_STRING_LENGTH);
         for i in 0..=8 {
             string.push_str(&self.board[i].to_string_compact());
         }
-        string.push_str("\n");
+        string.push('\n');
         string
     }
 }
@@ -728,17 +1048,7 @@ impl<B: Borrow<str>> From<B> for SudokuBoard {
 
 /// Returns an iterator over all 81 of the board's (row, col) indices from (0, 0) to (8, 8)
 fn board_indices() -> impl Iterator<Item = (usize, usize)> {
-    repeat(0)
-        .take(9)
-        .chain(repeat(1).take(9))
-        .chain(repeat(2).take(9))
-        .chain(repeat(3).take(9))
-        .chain(repeat(4).take(9))
-        .chain(repeat(5).take(9))
-        .chain(repeat(6).take(9))
-        .chain(repeat(7).take(9))
-        .chain(repeat(8).take(9))
-        .zip((0..9).cycle())
+    (0..9).flat_map(|row| (0..9).map(move |col| (row, col)))
 }
 
 impl Default for SudokuBoard {
@@ -747,6 +1057,8 @@ impl Default for SudokuBoard {
             board: [SudokuData::default(); 9],
             fillable_squares: Vec::with_capacity(81),
             is_solvable: true,
+            heuristic: Arc::new(MinimumRemainingValues),
+            cancel: None,
         }
     }
 }
@@ -835,6 +1147,63 @@ mod tests {
         let (count, mut solution) = board.find_one_compact();
         solution.pop();
         assert_eq!(count, expected_count);
-        assert!(expected_solutions.contains(&&solution.as_str()));
+        assert!(expected_solutions.contains(&solution.as_str()));
+    }
+
+    #[test]
+    fn check_unique() {
+        let mut board = SudokuBoard::from(
+            "--------------3-85--1-2-------5-7-----4---1---9-------5------73--2-1--------4---9",
+        );
+        match board.check_unique() {
+            Uniqueness::Unique(solution) => {
+                assert_eq!(81, solution.len());
+                assert!(solution.chars().all(|c| c.is_ascii_digit() && c != '0'));
+            }
+            other => panic!("expected a unique solution, found {:?}", other),
+        }
+        let mut board = SudokuBoard::from(
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.",
+        );
+        match board.check_unique() {
+            Uniqueness::Multiple(first, second) => assert_ne!(first, second),
+            other => panic!("expected multiple solutions, found {:?}", other),
+        }
+        let mut board = SudokuBoard::from("11");
+        assert_eq!(Uniqueness::Unsolvable, board.check_unique());
+        let mut board = SudokuBoard::from(
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.",
+        );
+        assert_eq!(35, board.find_up_to(100).len());
+    }
+
+    #[test]
+    fn search_observed() {
+        let puzzle =
+            ".75.....4.1...5.7...8.7...9..2417...4.......1...8.24..3...9.7...5.3.4..88.....31.";
+        let mut board = SudokuBoard::from(puzzle);
+        let mut events = Vec::new();
+        let count = board.search_observed(true, &mut |_, event| events.push(event));
+        assert_eq!(10, count);
+        let solutions = events
+            .iter()
+            .filter(|event| matches!(event, SearchEvent::Solution { .. }))
+            .count();
+        assert_eq!(10, solutions);
+        assert_eq!(puzzle, board.to_string_puzzle());
+
+        let mut replayed = SudokuBoard::from(puzzle);
+        for event in &events {
+            match *event {
+                SearchEvent::Fill {
+                    row, col, value, ..
+                } => replayed.fill(&SudokuSquare::new(row, col, box_index(row, col)), value),
+                SearchEvent::Clear { row, col, .. } => {
+                    replayed.clear(&SudokuSquare::new(row, col, box_index(row, col)))
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(puzzle, replayed.to_string_puzzle());
     }
 }
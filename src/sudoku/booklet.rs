@@ -0,0 +1,320 @@
+//! Lays out printable booklets of puzzles as PDF documents.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Puzzle pages    a title, then N puzzles per page, each labeled with its number and difficulty
+//!  Answer pages    the solution of every puzzle in the same order, drawn smaller, at least 6 per page
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Every page is numbered at the bottom. In answers, the givens are bold and the rest of the solution is gray.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::SudokuBoard;
+use crate::sudoku::generate;
+use crate::sudoku::grade;
+use crate::sudoku::pdf::{self, Font, Page, Pdf};
+use crate::sudoku::random::mix;
+use rayon::prelude::*;
+use std::io;
+
+/// The names of every paper size, as accepted by [Options](struct.Options.html).
+pub const PAPERS: [&str; 2] = ["letter", "a4"];
+
+/// The fewest solutions drawn on each answer page.
+const MIN_ANSWERS_PER_PAGE: usize = 6;
+
+/// The space around the edges of every page, in points.
+const MARGIN: f64 = 54.0;
+
+/// The smallest grid worth printing, in points, which limits how many puzzles fit on a page.
+const MIN_GRID_SIZE: f64 = 72.0;
+
+/// A puzzle in a booklet along with its solution and difficulty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The puzzle as a string of 81 characters, with `.` for each empty square.
+    pub puzzle: String,
+    /// The unique solution of the puzzle.
+    pub solution: String,
+    /// One of [GRADES](../grade/constant.GRADES.html).
+    pub level: &'static str,
+}
+
+/// How a booklet is laid out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// The heading at the top of every puzzle page.
+    pub title: String,
+    /// The number of puzzles on each puzzle page.
+    pub per_page: usize,
+    /// One of [PAPERS](constant.PAPERS.html).
+    pub paper: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            title: String::from("Sudoku"),
+            per_page: 4,
+            paper: String::from("letter"),
+        }
+    }
+}
+
+/// Solves and grades every puzzle, failing on the first puzzle without a unique solution.
+pub fn entries(puzzles: &[String]) -> io::Result<Vec<Entry>> {
+    puzzles
+        .par_iter()
+        .enumerate()
+        .map(|(index, puzzle)| {
+            let (grade, solution) = grade::grade(puzzle)
+                .and_then(|grade| {
+                    Some((
+                        grade,
+                        SudokuBoard::from(puzzle.as_str()).find_up_to(1).pop()?,
+                    ))
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("puzzle {} does not have a unique solution", index + 1),
+                    )
+                })?;
+            Ok(Entry {
+                puzzle: puzzle.clone(),
+                solution,
+                level: grade.level,
+            })
+        })
+        .collect()
+}
+
+/// Generates `count` puzzles that depend only on the seed, along with their solutions and difficulties.
+pub fn generated(count: usize, seed: u64) -> Vec<Entry> {
+    (0..count as u64)
+        .into_par_iter()
+        .map(|index| {
+            let (puzzle, solution) = generate::generate(mix(&[seed, index]));
+            let level = grade::grade(&puzzle)
+                .expect("generated puzzles have a unique solution")
+                .level;
+            Entry {
+                puzzle,
+                solution,
+                level,
+            }
+        })
+        .collect()
+}
+
+/// Lays out the puzzles followed by their answers.
+pub fn booklet(entries: &[Entry], options: &Options) -> io::Result<Pdf> {
+    let (width, height) = match options.paper.as_str() {
+        "letter" => (612.0, 792.0),
+        "a4" => (595.28, 841.89),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown paper size: {}", options.paper),
+            ))
+        }
+    };
+    if entries.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "there must be at least one puzzle in a booklet",
+        ));
+    }
+    if 0 == options.per_page {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "there must be at least one puzzle per page",
+        ));
+    }
+    let max_per_page = max_per_page(width, height);
+    if max_per_page < options.per_page {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "at most {} puzzles fit on one {} page",
+                max_per_page, options.paper
+            ),
+        ));
+    }
+    if !pdf::is_encodable(&options.title) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the title can only use Latin-1 letters and common punctuation: {}",
+                options.title
+            ),
+        ));
+    }
+    let answers_per_page = options.per_page.max(MIN_ANSWERS_PER_PAGE);
+    let puzzle_pages = pages(entries.len(), options.per_page);
+    let total = puzzle_pages + pages(entries.len(), answers_per_page);
+    let mut pdf = Pdf::new(width, height);
+    for (number, chunk) in entries.chunks(options.per_page).enumerate() {
+        let page = pdf.add_page();
+        let first = number * options.per_page;
+        heading(page, &options.title);
+        for (slot, entry) in chunk.iter().enumerate() {
+            let label = format!("Puzzle {} - {}", first + slot + 1, capitalize(entry.level));
+            let (x, y, size) = slot_box(slot, options.per_page, width, height);
+            page.text((x, y - 8.0), 12.0, Font::Bold, &label);
+            grid(page, (x, y), size, &entry.puzzle, &entry.puzzle);
+        }
+        footer(page, number + 1, total, width, height);
+    }
+    for (number, chunk) in entries.chunks(answers_per_page).enumerate() {
+        let page = pdf.add_page();
+        let first = number * answers_per_page;
+        heading(page, &format!("{} - Answers", options.title));
+        for (slot, entry) in chunk.iter().enumerate() {
+            let (x, y, size) = slot_box(slot, answers_per_page, width, height);
+            let label = format!("Puzzle {}", first + slot + 1);
+            page.text((x, y - 8.0), 10.0, Font::Bold, &label);
+            grid(page, (x, y), size, &entry.puzzle, &entry.solution);
+        }
+        footer(page, puzzle_pages + number + 1, total, width, height);
+    }
+    Ok(pdf)
+}
+
+/// Returns the number of pages needed to hold `count` items with `per_page` on each page.
+fn pages(count: usize, per_page: usize) -> usize {
+    count.div_ceil(per_page)
+}
+
+/// Returns the most puzzles that fit on a page while every grid is at least `MIN_GRID_SIZE` wide.
+fn max_per_page(width: f64, height: f64) -> usize {
+    (1..)
+        .take_while(|&per_page| MIN_GRID_SIZE <= slot_box(0, per_page, width, height).2)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Returns the top left corner and the size of the grid in a slot of the page, after room for its label.
+fn slot_box(slot: usize, per_page: usize, width: f64, height: f64) -> (f64, f64, f64) {
+    let columns = match per_page {
+        1 | 2 => 1,
+        3..=6 => 2,
+        _ => 3,
+    };
+    let rows = per_page.div_ceil(columns);
+    let (top, bottom) = (MARGIN + 36.0, height - MARGIN - 18.0);
+    let slot_width = (width - 2.0 * MARGIN) / columns as f64;
+    let slot_height = (bottom - top) / rows as f64;
+    let size = (slot_width - 24.0).min(slot_height - 36.0);
+    let (row, column) = (slot / columns, slot % columns);
+    let x = MARGIN + column as f64 * slot_width + (slot_width - size) / 2.0;
+    let y = top + row as f64 * slot_height + (slot_height - size) / 2.0 + 8.0;
+    (x, y, size)
+}
+
+fn heading(page: &mut Page, title: &str) {
+    page.text((MARGIN, MARGIN + 12.0), 18.0, Font::Bold, title);
+}
+
+fn footer(page: &mut Page, number: usize, total: usize, width: f64, height: f64) {
+    let text = format!("{} / {}", number, total);
+    // Helvetica digits are 0.556 em wide, and the spaces and slash 0.278 em each.
+    let digits = text.chars().filter(char::is_ascii_digit).count() as f64;
+    let text_width = 10.0 * (0.556 * digits + 0.278 * 3.0);
+    let at = ((width - text_width) / 2.0, height - MARGIN + 12.0);
+    page.text(at, 10.0, Font::Regular, &text);
+}
+
+/// Draws a grid with its top left corner at `(x, y)`, with the givens in bold and other values in gray.
+fn grid(page: &mut Page, (x, y): (f64, f64), size: f64, givens: &str, values: &str) {
+    let cell = size / 9.0;
+    page.gray(0.6);
+    for index in (1..9).filter(|index| 0 != index % 3) {
+        let at = index as f64 * cell;
+        page.line((x + at, y), (x + at, y + size), 0.5);
+        page.line((x, y + at), (x + size, y + at), 0.5);
+    }
+    page.gray(0.0);
+    for index in [3, 6] {
+        let at = index as f64 * cell;
+        page.line((x + at, y), (x + at, y + size), 1.5);
+        page.line((x, y + at), (x + size, y + at), 1.5);
+    }
+    page.rect((x, y), (size, size), 2.0);
+    let font_size = cell * 0.6;
+    for (index, (given, value)) in givens.chars().zip(values.chars()).enumerate() {
+        if !value.is_ascii_digit() || '0' == value {
+            continue;
+        }
+        let (row, col) = ((index / 9) as f64, (index % 9) as f64);
+        // A digit is 0.556 em wide and its top is about 0.7 em above the baseline.
+        let at = (
+            x + (col + 0.5) * cell - 0.278 * font_size,
+            y + (row + 0.5) * cell + 0.35 * font_size,
+        );
+        if given == value {
+            page.text(at, font_size, Font::Bold, &value.to_string());
+        } else {
+            page.gray(0.35);
+            page.text(at, font_size, Font::Regular, &value.to_string());
+            page.gray(0.0);
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn pages_and_answers() {
+        let mut all = entries(&vec![String::from(PUZZLE); 3]).unwrap();
+        assert_eq!("easy", all[0].level);
+        assert!(!all[0].solution.contains('.'));
+        all.extend(generated(2, 9));
+        assert_eq!(all[3..], generated(2, 9)[..]);
+        let options = Options {
+            per_page: 2,
+            ..Options::default()
+        };
+        let pdf = booklet(&all, &options).unwrap();
+        assert_eq!(3 + 1, pdf.len());
+        let text = String::from_utf8(pdf.to_bytes()).unwrap();
+        assert!(text.contains("(Puzzle 5 - "));
+        assert!(text.contains("(Sudoku - Answers) Tj"));
+        assert!(text.contains("(4 / 4) Tj"));
+        assert!(entries(&[String::from("1")]).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_crowded_booklets() {
+        assert!(booklet(&[], &Options::default()).is_err());
+        let entries = generated(1, 9);
+        assert_eq!(15, max_per_page(612.0, 792.0));
+        let crowded = Options {
+            per_page: 16,
+            ..Options::default()
+        };
+        assert!(booklet(&entries, &crowded).is_err());
+        let full = Options {
+            per_page: 15,
+            ..Options::default()
+        };
+        assert_eq!(2, booklet(&entries, &full).unwrap().len());
+        let unencodable = Options {
+            title: String::from("数独"),
+            ..Options::default()
+        };
+        assert!(booklet(&entries, &unencodable).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! A cell-centric search engine that keeps the candidates of every square instead of the values present in each house.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  masks        SudokuBoard: nine u64s with row/col/box "present" masks and four-bit values.
+//!               The options of a square are recomputed from three masks every time they are needed.
+//!  candidates   CandidateBoard: one u16 of candidates per square and a table of the 20 peers of each square.
+//!               Filling a square removes its value from the candidates of its peers once, and a search
+//!               stops as soon as any empty peer is left without candidates.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  The candidates engine fills squares with a single candidate in place, then branches on the square
+//!  with the fewest candidates, the first one in row-major order winning ties, like the mrv heuristic.
+//!  Each branch works on a copy of the board, which is small enough that copying is cheaper than undoing.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use rayon::prelude::*;
+use std::borrow::Borrow;
+
+/// The names of every search engine.
+pub const ENGINES: [&str; 2] = ["masks", "candidates"];
+
+/// Every candidate from 1 to 9, where bit `value - 1` stands for `value`.
+const ALL: u16 = 0b1_1111_1111;
+
+/// The number of branching levels that are searched in parallel before the search turns sequential.
+const PARALLEL_DEPTH: usize = 4;
+
+/// The 20 squares that share a row, column, or box with each square, excluding the square itself.
+static PEERS: [[u8; 20]; 81] = peers();
+
+const fn peers() -> [[u8; 20]; 81] {
+    let mut table = [[0; 20]; 81];
+    let mut square = 0;
+    while square < 81 {
+        let (row, col) = (square / 9, square % 9);
+        let mut count = 0;
+        let mut other = 0;
+        while other < 81 {
+            let (other_row, other_col) = (other / 9, other % 9);
+            let same_box = row / 3 == other_row / 3 && col / 3 == other_col / 3;
+            if other != square && (row == other_row || col == other_col || same_box) {
+                table[square][count] = other as u8;
+                count += 1;
+            }
+            other += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+/// What the search does after filling every square with a single candidate.
+enum Next {
+    /// Every square is filled.
+    Solved,
+    /// Some empty square has no candidates left.
+    DeadEnd,
+    /// The square with the fewest candidates has more than one, so the search branches on it.
+    Branch(usize),
+}
+
+/// A board that tracks the remaining candidates of every square.
+#[derive(Clone, Copy, Debug)]
+pub struct CandidateBoard {
+    values: [u8; 81],
+    candidates: [u16; 81],
+    /// Bit `square` is set while that square is empty.
+    empty: u128,
+    is_solvable: bool,
+}
+
+impl Default for CandidateBoard {
+    fn default() -> Self {
+        CandidateBoard {
+            values: [0; 81],
+            candidates: [ALL; 81],
+            empty: (1 << 81) - 1,
+            is_solvable: true,
+        }
+    }
+}
+
+impl CandidateBoard {
+    /// Writes a value into an empty square and removes it from the candidates of every peer.
+    /// Returns false if the value was not a candidate or if an empty peer is left without candidates.
+    fn place(&mut self, square: usize, value: u8) -> bool {
+        let bit = 1 << (value - 1);
+        if 0 == self.candidates[square] & bit {
+            return false;
+        }
+        self.values[square] = value;
+        self.candidates[square] = 0;
+        self.empty &= !(1 << square);
+        let mut is_consistent = true;
+        for &peer in PEERS[square].iter() {
+            let peer = peer as usize;
+            self.candidates[peer] &= !bit;
+            is_consistent &= 0 == self.empty & (1 << peer) || 0 != self.candidates[peer];
+        }
+        is_consistent
+    }
+
+    /// Returns the empty square with the fewest candidates, or `None` if the board is full.
+    fn next_square(&self) -> Option<usize> {
+        let (mut best, mut fewest) = (None, u32::MAX);
+        let mut empty = self.empty;
+        while 0 != empty {
+            let square = empty.trailing_zeros() as usize;
+            empty &= empty - 1;
+            let count = self.candidates[square].count_ones();
+            if count < fewest {
+                best = Some(square);
+                fewest = count;
+                if count <= 1 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    /// Returns the board after writing a value into a square, or `None` if that leaves no solution.
+    fn with(&self, square: usize, value: u8) -> Option<Self> {
+        let mut board = *self;
+        if board.place(square, value) {
+            Some(board)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the values that are still candidates of a square, in increasing order.
+    fn candidates(&self, square: usize) -> impl Iterator<Item = u8> {
+        let mut candidates = self.candidates[square];
+        std::iter::from_fn(move || {
+            if 0 == candidates {
+                return None;
+            }
+            let value = candidates.trailing_zeros() as u8 + 1;
+            candidates &= candidates - 1;
+            Some(value)
+        })
+    }
+
+    /// Fills every empty square that has a single candidate, then returns what the search does next.
+    fn fill_singles(&mut self) -> Next {
+        loop {
+            let square = match self.next_square() {
+                Some(square) => square,
+                None => return Next::Solved,
+            };
+            let candidates = self.candidates[square];
+            if 1 < candidates.count_ones() {
+                return Next::Branch(square);
+            }
+            if !self.place(square, candidates.trailing_zeros() as u8 + 1) {
+                return Next::DeadEnd;
+            }
+        }
+    }
+
+    /// Counts the number of solutions, searching the first branching levels in parallel.
+    pub fn count_solutions(&self) -> usize {
+        if self.is_solvable {
+            self.count_from(0)
+        } else {
+            0
+        }
+    }
+
+    fn count_from(mut self, depth: usize) -> usize {
+        let square = match self.fill_singles() {
+            Next::Solved => return 1,
+            Next::DeadEnd => return 0,
+            Next::Branch(square) => square,
+        };
+        let count = |value| match self.with(square, value) {
+            Some(board) => board.count_from(depth + 1),
+            None => 0,
+        };
+        if depth < PARALLEL_DEPTH {
+            let values: Vec<u8> = self.candidates(square).collect();
+            values.into_par_iter().map(count).sum()
+        } else {
+            self.candidates(square).map(count).sum()
+        }
+    }
+
+    /// Finds up to `limit` solutions sequentially, each as a string of 81 digits.
+    pub fn find_up_to(&self, limit: usize) -> Vec<String> {
+        let mut solutions = Vec::new();
+        if self.is_solvable && 0 < limit {
+            self.find_from(limit, &mut solutions);
+        }
+        solutions
+    }
+
+    fn find_from(mut self, limit: usize, solutions: &mut Vec<String>) {
+        let square = match self.fill_singles() {
+            Next::Solved => return solutions.push(self.to_string_puzzle()),
+            Next::DeadEnd => return,
+            Next::Branch(square) => square,
+        };
+        for value in self.candidates(square) {
+            if limit <= solutions.len() {
+                return;
+            }
+            if let Some(board) = self.with(square, value) {
+                board.find_from(limit, solutions);
+            }
+        }
+    }
+
+    /// Finds every solution, searching the first branching levels in parallel.
+    /// Solutions are in the order a sequential search would find them.
+    pub fn find_all(&self) -> Vec<String> {
+        if self.is_solvable {
+            self.find_all_from(0)
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn find_all_from(mut self, depth: usize) -> Vec<String> {
+        let square = match self.fill_singles() {
+            Next::Solved => return vec![self.to_string_puzzle()],
+            Next::DeadEnd => return Vec::new(),
+            Next::Branch(square) => square,
+        };
+        let find = |value| match self.with(square, value) {
+            Some(board) => board.find_all_from(depth + 1),
+            None => Vec::new(),
+        };
+        if depth < PARALLEL_DEPTH {
+            let values: Vec<u8> = self.candidates(square).collect();
+            values.into_par_iter().flat_map(find).collect()
+        } else {
+            self.candidates(square).flat_map(find).collect()
+        }
+    }
+
+    /// Formats the board as a string of 81 characters, with `.` for each empty square.
+    pub fn to_string_puzzle(&self) -> String {
+        self.values
+            .iter()
+            .map(|&value| match value {
+                0 => '.',
+                value => (b'0' + value) as char,
+            })
+            .collect()
+    }
+}
+
+impl<B: Borrow<str>> From<B> for CandidateBoard {
+    /// Creates a board from a string the same way as [SudokuBoard](../board/struct.SudokuBoard.html):
+    /// whitespace is ignored and every other character that is not a digit is a blank square.
+    fn from(input: B) -> Self {
+        input
+            .borrow()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_digit(10).unwrap_or(0) as u8)
+            .take(81)
+            .enumerate()
+            .fold(CandidateBoard::default(), |mut board, (square, value)| {
+                if 0 != value && !board.place(square, value) {
+                    board.is_solvable = false;
+                }
+                board
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::SudokuBoard;
+
+    const PUZZLES: [&str; 4] = [
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.",
+        ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.",
+        "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9",
+        "11",
+    ];
+
+    #[test]
+    fn agrees_with_masks() {
+        for puzzle in PUZZLES.iter() {
+            let board = CandidateBoard::from(*puzzle);
+            let mut masks = SudokuBoard::from(*puzzle);
+            assert_eq!(
+                masks.count_solutions(),
+                board.count_solutions(),
+                "{}",
+                puzzle
+            );
+            let mut expected = masks.find_up_to(100);
+            let mut found = board.find_all();
+            assert_eq!(found.len(), board.count_solutions());
+            expected.sort();
+            found.sort();
+            assert_eq!(expected, found, "{}", puzzle);
+        }
+        let board = CandidateBoard::from(PUZZLES[1]);
+        assert_eq!(2, board.find_up_to(2).len());
+        assert_eq!(board.find_all()[..3], board.find_up_to(3)[..]);
+    }
+
+    #[test]
+    fn peer_table() {
+        for (square, peers) in PEERS.iter().enumerate() {
+            assert!(!peers.contains(&(square as u8)));
+            let mut sorted = peers.to_vec();
+            sorted.dedup();
+            assert_eq!(20, sorted.len());
+        }
+        assert_eq!([1, 2, 3, 4, 5, 6, 7, 8, 9], PEERS[0][..9]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Canonical forms of puzzles, so that equivalent puzzles can be recognized and deduplicated.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Two puzzles are equivalent if one can be turned into the other by any combination of:
+//!    - relabeling the digits                      - swapping bands (groups of three rows)
+//!    - swapping rows within a band                - swapping stacks (groups of three columns)
+//!    - swapping columns within a stack            - transposing the grid
+//!  Rotations and reflections are combinations of these.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  The canonical form is the lexicographically smallest puzzle string among every equivalent puzzle,
+//!  where `.` sorts before every digit and digits are relabeled in order of their first appearance.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::SudokuBoard;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+
+/// The six permutations of three elements.
+const PERMUTATIONS_3: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// Returns the canonical form of the board as an 81-character puzzle string with `.` for each empty square.
+/// Two boards are equivalent if and only if they have the same canonical form.
+pub fn canonical_form(board: &SudokuBoard) -> String {
+    let grid = to_grid(board);
+    let grids = [grid, transpose(&grid)];
+    let lines = line_permutations();
+    // Every digit in the first row is relabeled in increasing order, so the smallest first row only depends on
+    // where its empty squares are. Only rows and column orders that achieve the emptiest first row can win.
+    let mut first_rows = Vec::new();
+    for grid in grids.iter() {
+        for row in 0..9 {
+            let pattern = |cols: &[usize; 9]| cols.map(|col| 0 != grid[row * 9 + col]);
+            let smallest = lines
+                .iter()
+                .map(pattern)
+                .min()
+                .expect("permutations are never empty");
+            let cols: Vec<&[usize; 9]> = lines
+                .iter()
+                .filter(|cols| smallest == pattern(cols))
+                .collect();
+            first_rows.push((smallest, grid, row, cols));
+        }
+    }
+    let smallest = first_rows.iter().map(|(pattern, ..)| *pattern).min();
+    first_rows.retain(|(pattern, ..)| Some(*pattern) == smallest);
+    let best = first_rows
+        .par_iter()
+        .flat_map(|(_, grid, first, cols)| {
+            lines
+                .par_iter()
+                .filter(move |rows| rows[0] == *first)
+                .map(move |rows| (grid, rows, cols))
+        })
+        .map(|(grid, rows, cols)| {
+            let mut permuted = [0; 81];
+            for (row, &from) in rows.iter().enumerate() {
+                permuted[row * 9..row * 9 + 9].copy_from_slice(&grid[from * 9..from * 9 + 9]);
+            }
+            let mut best = [u8::MAX; 81];
+            for cols in cols.iter() {
+                relabel_if_smaller(&permuted, cols, &mut best);
+            }
+            best
+        })
+        .min()
+        .expect("there is always at least one permutation");
+    best.iter()
+        .map(|&value| match value {
+            0 => '.',
+            _ => (b'0' + value) as char,
+        })
+        .collect()
+}
+
+/// Returns true if the two boards are equivalent.
+pub fn is_same(a: &SudokuBoard, b: &SudokuBoard) -> bool {
+    canonical_form(a) == canonical_form(b)
+}
+
+/// Returns the values of the board in row-major order, with 0 for each empty square.
+fn to_grid(board: &SudokuBoard) -> [u8; 81] {
+    let mut grid = [0; 81];
+    for (square, c) in grid.iter_mut().zip(board.to_string_puzzle().chars()) {
+        *square = c.to_digit(10).unwrap_or(0) as u8;
+    }
+    grid
+}
+
+fn transpose(grid: &[u8; 81]) -> [u8; 81] {
+    let mut transposed = [0; 81];
+    for row in 0..9 {
+        for col in 0..9 {
+            transposed[col * 9 + row] = grid[row * 9 + col];
+        }
+    }
+    transposed
+}
+
+/// Returns the 1296 orderings of nine rows (or columns) that keep each band (or stack) together.
+fn line_permutations() -> Vec<[usize; 9]> {
+    let mut lines = Vec::with_capacity(1296);
+    for bands in &PERMUTATIONS_3 {
+        for first in &PERMUTATIONS_3 {
+            for second in &PERMUTATIONS_3 {
+                for third in &PERMUTATIONS_3 {
+                    let mut order = [0; 9];
+                    for (band, within) in [first, second, third].iter().enumerate() {
+                        for (i, &line) in within.iter().enumerate() {
+                            order[band * 3 + i] = bands[band] * 3 + line;
+                        }
+                    }
+                    lines.push(order);
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Reorders the columns of the grid, relabels its digits in order of first appearance,
+/// and replaces `best` with the result if it is smaller. Stops as soon as the result is known to be larger.
+fn relabel_if_smaller(grid: &[u8; 81], cols: &[usize; 9], best: &mut [u8; 81]) {
+    let mut labels = [0; 10];
+    let mut next_label = 1;
+    let mut is_smaller = false;
+    for row in 0..9 {
+        for (col, &from) in cols.iter().enumerate() {
+            let value = grid[row * 9 + from];
+            let label = match value {
+                0 => 0,
+                _ if 0 == labels[value as usize] => {
+                    labels[value as usize] = next_label;
+                    next_label += 1;
+                    next_label - 1
+                }
+                _ => labels[value as usize],
+            };
+            let index = row * 9 + col;
+            if !is_smaller {
+                match label.cmp(&best[index]) {
+                    Ordering::Greater => return,
+                    Ordering::Less => is_smaller = true,
+                    Ordering::Equal => continue,
+                }
+            }
+            best[index] = label;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::minimize::without_clue;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn equivalent_puzzles() {
+        let board = SudokuBoard::from(PUZZLE);
+        let canonical = canonical_form(&board);
+        assert_eq!(81, canonical.len());
+        assert_eq!(
+            canonical,
+            canonical_form(&SudokuBoard::from(canonical.as_str()))
+        );
+        let relabeled: String = PUZZLE
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(digit) => (b'0' + (digit % 9 + 1) as u8) as char,
+                None => c,
+            })
+            .collect();
+        let transposed: String = (0..81)
+            .map(|i| PUZZLE.as_bytes()[(i % 9) * 9 + i / 9] as char)
+            .collect();
+        let rotated: String = (0..81)
+            .map(|i| PUZZLE.as_bytes()[(8 - i % 9) * 9 + i / 9] as char)
+            .collect();
+        for other in &[relabeled, transposed, rotated] {
+            assert!(is_same(&board, &SudokuBoard::from(other.as_str())));
+        }
+        let different = without_clue(PUZZLE, 1);
+        assert!(!is_same(&board, &SudokuBoard::from(different.as_str())));
+    }
+}
@@ -0,0 +1,238 @@
+//! Records the progress of a long count so that it can resume after the process stops.
+//! ```text
+//! {
+//!   "puzzle": "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9",
+//!   "shard": null,
+//!   "branches": 261,
+//!   "completed": [{"branch": 0, "count": 12}, {"branch": 1, "count": 0}, {"branch": 2, "count": 7}]
+//! }
+//! ```
+//! The search is split into the branches of [shard::branches](../shard/fn.branches.html), which are counted one at a
+//! time. After each branch the file is rewritten with every completed branch and its count, so a resumed count
+//! skips them and still reaches the same total. The file is replaced atomically, so a crash never leaves it half written.
+
+use crate::sudoku::shard;
+use crate::sudoku::shard::Shard;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The number of branches a count without a shard is split into.
+const BRANCHES: usize = 256;
+
+/// The completed branches of a count and the number of solutions in each.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub puzzle: String,
+    pub shard: Option<Shard>,
+    /// The number of branches the search is split into.
+    pub branches: usize,
+    /// The number of solutions in each completed branch, by the position of the branch.
+    pub completed: BTreeMap<usize, usize>,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint without completed branches.
+    pub fn new(puzzle: &str, shard: Option<Shard>, branches: usize) -> Self {
+        Checkpoint {
+            puzzle: String::from(puzzle),
+            shard,
+            branches,
+            completed: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the checkpoint recorded in `path` if resuming, after checking that it was written for the same
+    /// puzzle, shard, and branches. Otherwise returns a new checkpoint, failing if `path` already exists so that
+    /// a finished or interrupted count is never overwritten by accident.
+    pub fn start(
+        path: &str,
+        puzzle: &str,
+        shard: Option<Shard>,
+        branches: usize,
+        resume: bool,
+    ) -> io::Result<Self> {
+        if resume {
+            let checkpoint = Checkpoint::read(path)?;
+            checkpoint.check(puzzle, shard, branches)?;
+            return Ok(checkpoint);
+        }
+        if Path::new(path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists: continue it with --resume or remove it to start over",
+                    path
+                ),
+            ));
+        }
+        Ok(Checkpoint::new(puzzle, shard, branches))
+    }
+
+    /// Reads a checkpoint written by [write](#method.write).
+    pub fn read(path: &str) -> io::Result<Self> {
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a checkpoint: {}", path, message),
+            )
+        };
+        let value: Value = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| invalid(&e.to_string()))?;
+        let puzzle = value["puzzle"]
+            .as_str()
+            .ok_or_else(|| invalid("missing puzzle"))?;
+        let shard = match &value["shard"] {
+            Value::Null => None,
+            shard => Some(
+                shard
+                    .as_str()
+                    .ok_or_else(|| invalid("shard is not a string"))?
+                    .parse()
+                    .map_err(|e: String| invalid(&e))?,
+            ),
+        };
+        let branches = value["branches"]
+            .as_u64()
+            .ok_or_else(|| invalid("missing branches"))?;
+        let mut checkpoint = Checkpoint::new(puzzle, shard, branches as usize);
+        for entry in value["completed"].as_array().into_iter().flatten() {
+            match (entry["branch"].as_u64(), entry["count"].as_u64()) {
+                (Some(branch), Some(count)) => {
+                    checkpoint.completed.insert(branch as usize, count as usize);
+                }
+                _ => return Err(invalid("completed branches need a branch and a count")),
+            }
+        }
+        Ok(checkpoint)
+    }
+
+    /// Writes the checkpoint to a temporary file next to `path`, then renames it over `path`.
+    pub fn write(&self, path: &str) -> io::Result<()> {
+        let completed: Vec<Value> = self
+            .completed
+            .iter()
+            .map(|(branch, count)| json!({ "branch": branch, "count": count }))
+            .collect();
+        let value = json!({
+            "puzzle": self.puzzle,
+            "shard": self.shard.map(|shard| shard.to_string()),
+            "branches": self.branches,
+            "completed": completed,
+        });
+        let temporary = format!("{}.tmp", path);
+        fs::write(&temporary, format!("{:#}\n", value))?;
+        fs::rename(&temporary, path)
+    }
+
+    /// Returns the total number of solutions in the completed branches.
+    pub fn count(&self) -> usize {
+        self.completed.values().sum()
+    }
+
+    /// Fails unless the checkpoint was written for the same puzzle, shard, and branches.
+    pub fn check(&self, puzzle: &str, shard: Option<Shard>, branches: usize) -> io::Result<()> {
+        if self.puzzle != puzzle || self.shard != shard || self.branches != branches {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the checkpoint was written for a different puzzle or shard",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the branches that a count of the puzzle, or of one of its shards, is split into.
+pub fn branches(puzzle: &str, shard: Option<Shard>) -> Vec<String> {
+    match shard {
+        Some(shard) => shard.branches(puzzle),
+        None => shard::branches(puzzle, BRANCHES),
+    }
+}
+
+/// Counts every branch that is not completed yet, recording each one in the checkpoint as it finishes
+/// and writing the checkpoint to `path` if there is one. Returns the total count of every branch.
+pub fn count<F>(
+    branches: &[String],
+    checkpoint: &mut Checkpoint,
+    path: Option<&str>,
+    count_branch: F,
+) -> io::Result<usize>
+where
+    F: Fn(&str) -> usize,
+{
+    for (index, branch) in branches.iter().enumerate() {
+        if checkpoint.completed.contains_key(&index) {
+            continue;
+        }
+        checkpoint.completed.insert(index, count_branch(branch));
+        if let Some(path) = path {
+            checkpoint.write(path)?;
+        }
+    }
+    Ok(checkpoint.count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::SudokuBoard;
+    use std::cell::Cell;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+
+    #[test]
+    fn resumes_with_the_same_count() {
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let branches = branches(PUZZLE, None);
+        let count_branch = |branch: &str| SudokuBoard::from(branch).count_solutions();
+
+        // Count the first half of the branches, as if the process stopped there.
+        let mut checkpoint = Checkpoint::new(PUZZLE, None, branches.len());
+        let half = branches.len() / 2;
+        count(&branches[..half], &mut checkpoint, Some(path), count_branch).unwrap();
+
+        let mut resumed = Checkpoint::read(path).unwrap();
+        assert_eq!(checkpoint, resumed);
+        resumed.check(PUZZLE, None, branches.len()).unwrap();
+        assert!(resumed
+            .check(PUZZLE, Some(Shard { index: 0, count: 2 }), branches.len())
+            .is_err());
+        let calls = Cell::new(0);
+        let total = count(&branches, &mut resumed, Some(path), |branch| {
+            calls.set(calls.get() + 1);
+            count_branch(branch)
+        })
+        .unwrap();
+        assert_eq!(35, total);
+        assert_eq!(branches.len() - half, calls.get());
+        assert_eq!(35, Checkpoint::read(path).unwrap().count());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn starts_only_over_missing_files() {
+        let path = std::env::temp_dir().join(format!("started-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let branches = branches(PUZZLE, None).len();
+        let mut checkpoint = Checkpoint::start(path, PUZZLE, None, branches, false).unwrap();
+        assert!(checkpoint.completed.is_empty());
+        assert!(Checkpoint::start(path, PUZZLE, None, branches, true).is_err());
+
+        checkpoint.completed.insert(0, 3);
+        checkpoint.write(path).unwrap();
+        let error = Checkpoint::start(path, PUZZLE, None, branches, false).unwrap_err();
+        assert_eq!(io::ErrorKind::AlreadyExists, error.kind());
+        assert_eq!(
+            checkpoint,
+            Checkpoint::start(path, PUZZLE, None, branches, true).unwrap()
+        );
+        let shard = Some(Shard { index: 0, count: 2 });
+        assert!(Checkpoint::start(path, PUZZLE, shard, branches, true).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -35,37 +35,37 @@ impl SudokuData {
     /// Fills a given square with a value.
     pub fn fill_square(&mut self, value: usize, col: usize) {
         self.clear_square(col);
-        self.0 = self.0 | shift_to_square(value, col)
+        self.0 |= shift_to_square(value, col)
     }
 
     /// Marks a value as being present in the row.
     pub fn mark_in_row(&mut self, value: usize) {
-        self.0 = self.0 | shift_to_row(as_bit(value));
+        self.0 |= shift_to_row(as_bit(value));
     }
 
     /// Marks a value as being present in the column.
     pub fn mark_in_col(&mut self, value: usize) {
-        self.0 = self.0 | shift_to_col(as_bit(value));
+        self.0 |= shift_to_col(as_bit(value));
     }
 
     /// Marks a value as being present in the box.
     pub fn mark_in_box(&mut self, value: usize) {
-        self.0 = self.0 | shift_to_box(as_bit(value));
+        self.0 |= shift_to_box(as_bit(value));
     }
 
     /// Unmarks a value from being present in the row.
     pub fn unmark_from_row(&mut self, value: usize) {
-        self.0 = self.0 & shift_to_row_inverse(as_bit_inverse(value));
+        self.0 &= shift_to_row_inverse(as_bit_inverse(value));
     }
 
     /// Unmarks a value from being present in the column.
     pub fn unmark_from_col(&mut self, value: usize) {
-        self.0 = self.0 & shift_to_col_inverse(as_bit_inverse(value));
+        self.0 &= shift_to_col_inverse(as_bit_inverse(value));
     }
 
     /// Unmarks a value from being present in the box.
     pub fn unmark_from_box(&mut self, value: usize) {
-        self.0 = self.0 & shift_to_box_inverse(as_bit_inverse(value));
+        self.0 &= shift_to_box_inverse(as_bit_inverse(value));
     }
 
     /// Returns a set of bits representing the values currently in the row.
@@ -83,6 +83,11 @@ impl SudokuData {
         bitwise::values_in_box(self.0)
     }
 
+    /// Returns the raw bits of the row.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
     /// Returns the value at a particular square.
     pub fn value_at(&self, col: usize) -> u64 {
         value_in_square(self.0, col)
@@ -94,6 +99,7 @@ impl SudokuData {
     }
 
     /// Formats the row as it would look on a sudoku board.
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
         format!(
             "  ║ {} │ {} │ {} ║ {} │ {} │ {} ║ {} │ {} │ {} ║\n",
@@ -109,6 +115,28 @@ impl SudokuData {
         )
     }
 
+    /// Formats the row as it would look on a sudoku board, with brackets around each highlighted square.
+    pub fn to_string_highlighted(&self, highlighted: &[bool]) -> String {
+        let squares: Vec<String> = (0..=8)
+            .map(|col| match highlighted.get(col) {
+                Some(true) => format!("[{}]", self.format_square(col)),
+                _ => format!(" {} ", self.format_square(col)),
+            })
+            .collect();
+        format!(
+            "  ║{}│{}│{}║{}│{}│{}║{}│{}│{}║\n",
+            squares[0],
+            squares[1],
+            squares[2],
+            squares[3],
+            squares[4],
+            squares[5],
+            squares[6],
+            squares[7],
+            squares[8],
+        )
+    }
+
     /// Formats every value in the row in a single line of 9 digits.
     pub fn to_string_compact(&self) -> String {
         let mut string = String::with_capacity(9);
@@ -117,6 +145,16 @@ impl SudokuData {
         }
         string
     }
+
+    /// Formats every value in the row in a single line of 9 characters, with `.` for each empty square.
+    pub fn to_string_puzzle(&self) -> String {
+        (0..=8)
+            .map(|col| match self.value_at(col) {
+                0 => '.',
+                value => (b'0' + value as u8) as char,
+            })
+            .collect()
+    }
 }
 
 /// Displays the row as it would look on a sudoku board.
@@ -139,6 +177,7 @@ impl fmt::Display for SudokuData {
 }
 
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod tests {
     use super::*;
 
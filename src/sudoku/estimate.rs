@@ -0,0 +1,150 @@
+//! Estimates the number of solutions of puzzles that have too many to count, with Knuth's estimator.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  A probe walks one random path down the search tree, choosing squares like the search does and each
+//!  value uniformly. A path through squares with 4, 3, and 2 options is taken with probability 1/24, so
+//!  it stands for 24 leaves: the probe returns 24 if it ends in a solution and 0 if it gets stuck.
+//!  The mean of many probes is an unbiased estimate of the number of solutions.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+//! Probes run in batches across threads, each seeded from the seed and its own index, so an estimate only
+//! depends on the seed and the number of samples and never on the number of threads.
+
+use crate::sudoku::board::SudokuBoard;
+use crate::sudoku::random::{mix, SplitMix64};
+use rayon::prelude::*;
+
+/// The number of probes taken between checks of the relative error.
+const BATCH: usize = 1000;
+
+/// The z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// The running mean and variance of the probes taken so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Estimate {
+    /// The number of probes taken.
+    pub samples: usize,
+    /// The mean of the probes, which estimates the number of solutions.
+    pub mean: f64,
+    /// The sum of squared differences from the mean, for Welford's online variance.
+    squares: f64,
+}
+
+impl Estimate {
+    /// Adds the result of one probe.
+    pub fn add(&mut self, sample: f64) {
+        self.samples += 1;
+        let delta = sample - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.squares += delta * (sample - self.mean);
+    }
+
+    /// Returns the standard error of the mean.
+    pub fn standard_error(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+        (self.squares / (self.samples - 1) as f64 / self.samples as f64).sqrt()
+    }
+
+    /// Returns the 95% confidence interval of the number of solutions, which is never below 0.
+    pub fn interval(&self) -> (f64, f64) {
+        let margin = Z_95 * self.standard_error();
+        ((self.mean - margin).max(0.0), self.mean + margin)
+    }
+
+    /// Returns the half width of the 95% confidence interval as a fraction of the estimate.
+    pub fn relative_error(&self) -> f64 {
+        if 0.0 == self.mean {
+            return f64::INFINITY;
+        }
+        Z_95 * self.standard_error() / self.mean
+    }
+}
+
+/// Parses a number of probes, which must be at least 2 for the probes to have a spread and the estimate an interval.
+pub fn parse_samples(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(samples) if 2 <= samples => Ok(samples),
+        _ => Err(format!("the number of samples must be at least 2: {}", s)),
+    }
+}
+
+/// Estimates the number of solutions of a board from at least `samples` probes. With a target, probes
+/// continue until the relative error is at most the target or `max_samples` probes have been taken.
+pub fn estimate(
+    board: &SudokuBoard,
+    seed: u64,
+    samples: usize,
+    target: Option<f64>,
+    max_samples: usize,
+) -> Estimate {
+    let mut estimate = Estimate::default();
+    while estimate.samples < max_samples {
+        let is_done = match target {
+            Some(target) => samples <= estimate.samples && estimate.relative_error() <= target,
+            None => samples <= estimate.samples,
+        };
+        if is_done {
+            break;
+        }
+        let start = estimate.samples as u64;
+        let batch = BATCH.min(max_samples - estimate.samples);
+        let batch = match target {
+            Some(_) => batch,
+            None => batch.min(samples - estimate.samples),
+        };
+        let probes: Vec<f64> = (start..start + batch as u64)
+            .into_par_iter()
+            .map(|index| board.probe(&mut SplitMix64::new(mix(&[seed, index]))))
+            .collect();
+        for probe in probes {
+            estimate.add(probe);
+        }
+    }
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_known_counts() {
+        let puzzle =
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+        let board = SudokuBoard::from(puzzle);
+        let estimate = estimate(&board, 7, 20_000, None, 20_000);
+        assert_eq!(20_000, estimate.samples);
+        let (low, high) = estimate.interval();
+        assert!(low <= 35.0 && 35.0 <= high, "{:?}", estimate.interval());
+
+        let solved = SudokuBoard::from(puzzle).find_up_to(1).remove(0);
+        let estimate = super::estimate(&SudokuBoard::from(solved), 7, 10, None, 10);
+        assert_eq!((1.0, 1.0), estimate.interval());
+        assert_eq!(
+            0.0,
+            super::estimate(&SudokuBoard::from("11"), 7, 10, None, 10).mean
+        );
+    }
+
+    #[test]
+    fn stops_at_the_target_error() {
+        let board = SudokuBoard::from("");
+        let estimate = estimate(&board, 3, 1, Some(0.05), 1_000_000);
+        assert!(estimate.relative_error() <= 0.05);
+        assert!(estimate.samples < 1_000_000);
+        assert_eq!(0, estimate.samples % BATCH);
+        let (low, high) = estimate.interval();
+        assert!(low <= 6.670_903_752_021_073e21 && 6.670_903_752_021_073e21 <= high);
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(Ok(2), parse_samples("2"));
+        assert!(parse_samples("1").is_err());
+        assert!(parse_samples("0").is_err());
+        assert!(parse_samples("many").is_err());
+    }
+}
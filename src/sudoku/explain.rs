@@ -0,0 +1,56 @@
+//! Explains why a puzzle has no solution by finding a minimal set of givens that already has none.
+//! Puzzles are strings of 81 characters as returned by
+//! [to_string_puzzle](../board/struct.SudokuBoard.html#method.to_string_puzzle), with `.` for each empty square.
+//! Givens are removed one at a time, keeping each removal that leaves the puzzle without a solution, so
+//! removing any one of the givens that remain makes the puzzle solvable.
+
+use crate::sudoku::board::SudokuBoard;
+use crate::sudoku::minimize::{clues, without_clue};
+
+/// Returns true if the puzzle has at least one solution.
+pub fn is_solvable(puzzle: &str) -> bool {
+    !SudokuBoard::from(puzzle).find_up_to(1).is_empty()
+}
+
+/// Returns the index `(row * 9 + col)` of every given in a minimal set of givens without a solution,
+/// in row-major order, or `None` if the puzzle has a solution.
+pub fn conflict(puzzle: &str) -> Option<Vec<usize>> {
+    if is_solvable(puzzle) {
+        return None;
+    }
+    let mut core = String::from(puzzle);
+    for index in clues(puzzle) {
+        let candidate = without_clue(&core, index);
+        if !is_solvable(&candidate) {
+            core = candidate;
+        }
+    }
+    Some(clues(&core))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_a_minimal_conflict() {
+        // The only solution has 9 at r0c0, so a 6 there leaves no solution without repeating any value.
+        let puzzle =
+            "675.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+        let conflict = conflict(puzzle).unwrap();
+        assert!(conflict.contains(&0));
+        let core: String = (0..81)
+            .map(|index| match conflict.binary_search(&index) {
+                Ok(_) => puzzle.as_bytes()[index] as char,
+                Err(_) => '.',
+            })
+            .collect();
+        assert!(!is_solvable(&core));
+        for &index in &conflict {
+            assert!(is_solvable(&without_clue(&core, index)));
+        }
+        let repeated = SudokuBoard::from("1.......1").to_string_puzzle();
+        assert_eq!(Some(vec![0, 8]), super::conflict(&repeated));
+        assert_eq!(None, super::conflict(&without_clue(puzzle, 0)));
+    }
+}
@@ -0,0 +1,304 @@
+//! Finds the fewest clues to add to a puzzle with several solutions so that it has exactly one.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  One solution is kept, and every clue added is its value at an empty square. Another solution survives
+//!  the added clues only if it agrees with the kept one on all of them, so the added squares must include
+//!  a square where each other solution differs: a hitting set of the squares where they differ.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  build      The empty square with the most options is filled with its value in the kept solution
+//!             until the puzzle is unique, then every added clue the puzzle stays unique without is
+//!             removed again. Every search along the way records the other solutions it finds.
+//!  shrink     The smallest set of squares that hits every recorded solution is tried: either the puzzle
+//!             is unique with it, and no smaller set could be, or the search finds other solutions that
+//!             it misses, which are recorded for the next round. Rounds stop once no set smaller than
+//!             the best one found hits every recorded solution, which proves that one is the smallest.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+//! With a symmetry, each empty square and its mirror image are added together, so a symmetric pattern of
+//! clues stays symmetric. If proving the smallest set takes too long, the best set found so far is returned.
+
+use crate::sudoku::board::{SudokuBoard, SudokuSquare};
+use crate::sudoku::transform::{Reflection, Transform};
+
+/// The names of every symmetry the added clues can keep, as accepted by [symmetry](fn.symmetry.html).
+pub const SYMMETRIES: [&str; 6] = [
+    "none",
+    "rotational",
+    "horizontal",
+    "vertical",
+    "diagonal",
+    "anti-diagonal",
+];
+
+/// The number of solutions each search looks for, so that every search records several other solutions.
+const SOLUTIONS_PER_SEARCH: usize = 16;
+
+/// The number of rounds that try a smaller set before the best set so far is returned.
+const MAX_ROUNDS: usize = 200;
+
+/// The number of branches each search for the smallest hitting set may take.
+const MAX_BRANCHES: usize = 100_000;
+
+/// The clues that make a puzzle unique.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    /// The puzzle with the added clues, which has exactly one solution.
+    pub puzzle: String,
+    /// The index `(row * 9 + col)` of every added clue, in row-major order.
+    pub clues: Vec<usize>,
+    /// Whether no smaller set of squares (or pairs of squares, with a symmetry) would do.
+    pub is_minimum: bool,
+    /// The number of searches it took.
+    pub searches: usize,
+}
+
+/// Returns the symmetry with the given name, as listed in [SYMMETRIES](constant.SYMMETRIES.html),
+/// or `None` for `none` and unknown names. Rotational symmetry is a half turn.
+pub fn symmetry(name: &str) -> Option<Transform> {
+    match name {
+        "rotational" => Some(Transform::rotate(2)),
+        name => Reflection::from_name(name).map(Transform::reflect),
+    }
+}
+
+/// Finds clues to add to a puzzle string so that it has exactly one solution, keeping a symmetry if one is
+/// given. Returns `None` if the puzzle has no solution.
+pub fn fix_unique(puzzle: &str, symmetry: Option<&Transform>) -> Option<Fix> {
+    let puzzle = SudokuBoard::from(puzzle).to_string_puzzle();
+    let solution = SudokuBoard::from(puzzle.as_str()).find_up_to(1).pop()?;
+    let mut fixer = Fixer {
+        groups: groups(&puzzle, symmetry),
+        puzzle,
+        solution,
+        others: Vec::new(),
+        searches: 1,
+    };
+    let mut best = fixer.build();
+    let mut is_minimum = false;
+    for _ in 0..MAX_ROUNDS {
+        let min_size = lower_bound(&fixer.others);
+        let chosen = match smallest_hitting_set(&fixer.others, min_size, best) {
+            Some(Some(chosen)) => chosen,
+            Some(None) => {
+                is_minimum = true;
+                break;
+            }
+            None => break,
+        };
+        if fixer.is_unique(chosen) {
+            best = chosen;
+            is_minimum = true;
+            break;
+        }
+    }
+    let clues = fixer.clues(best);
+    Some(Fix {
+        puzzle: fixer.with_clues(&clues),
+        clues,
+        is_minimum,
+        searches: fixer.searches,
+    })
+}
+
+/// The search for clues that make one puzzle unique, with every other solution it has found so far.
+struct Fixer {
+    puzzle: String,
+    /// The solution whose values are added as clues.
+    solution: String,
+    /// The squares that are added together.
+    groups: Vec<Vec<usize>>,
+    /// The groups in which each other solution found differs from the kept one, as a bit for each group.
+    others: Vec<u128>,
+    searches: usize,
+}
+
+impl Fixer {
+    /// Adds the group with the most options until the puzzle is unique, then removes every added group
+    /// the puzzle stays unique without. Returns the remaining groups as a bit for each group.
+    fn build(&mut self) -> u128 {
+        let mut chosen = 0;
+        while !self.is_unique(chosen) {
+            let board = SudokuBoard::from(self.with_clues(&self.clues(chosen)));
+            let options = |group: &Vec<usize>| -> u32 {
+                group
+                    .iter()
+                    .map(|&index| board.count_options(&SudokuSquare::at(index / 9, index % 9)))
+                    .sum()
+            };
+            let best = (0..self.groups.len())
+                .filter(|&group| 0 == chosen & 1 << group)
+                .max_by_key(|&group| (options(&self.groups[group]), usize::MAX - group));
+            match best {
+                Some(group) => chosen |= 1 << group,
+                None => break,
+            }
+        }
+        for group in (0..self.groups.len()).rev() {
+            if 0 != chosen & 1 << group && self.is_unique(chosen & !(1 << group)) {
+                chosen &= !(1 << group);
+            }
+        }
+        chosen
+    }
+
+    /// Returns true if the puzzle with the chosen groups added has exactly one solution, and records
+    /// the other solutions found if it does not.
+    fn is_unique(&mut self, chosen: u128) -> bool {
+        self.searches += 1;
+        let puzzle = self.with_clues(&self.clues(chosen));
+        let found = SudokuBoard::from(puzzle).find_up_to(SOLUTIONS_PER_SEARCH);
+        let (groups, solution) = (&self.groups, &self.solution);
+        let others = found
+            .iter()
+            .filter(|&other| other != solution)
+            .map(|other| differences(groups, solution, other));
+        self.others.extend(others);
+        found.len() <= 1
+    }
+
+    /// Returns the squares of the chosen groups, in row-major order.
+    fn clues(&self, chosen: u128) -> Vec<usize> {
+        let mut clues: Vec<usize> = (0..self.groups.len())
+            .filter(|&group| 0 != chosen & 1 << group)
+            .flat_map(|group| self.groups[group].iter().copied())
+            .collect();
+        clues.sort_unstable();
+        clues
+    }
+
+    /// Returns the puzzle with the kept solution's value at each of the given squares.
+    fn with_clues(&self, clues: &[usize]) -> String {
+        self.puzzle
+            .char_indices()
+            .map(|(index, c)| match clues.binary_search(&index) {
+                Ok(_) => self.solution.as_bytes()[index] as char,
+                Err(_) => c,
+            })
+            .collect()
+    }
+}
+
+/// Returns the empty squares of a puzzle in the groups that are added together: each square alone, or with
+/// its mirror image under the symmetry.
+fn groups(puzzle: &str, symmetry: Option<&Transform>) -> Vec<Vec<usize>> {
+    let is_empty = |index: usize| b'.' == puzzle.as_bytes()[index];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for index in (0..81).filter(|&index| is_empty(index)) {
+        let mirror = symmetry.map_or(index, |symmetry| symmetry.source(index));
+        if mirror < index && is_empty(mirror) {
+            continue;
+        }
+        let mut group = vec![index];
+        if mirror != index && is_empty(mirror) {
+            group.push(mirror);
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+/// Returns the groups in which another solution differs from the kept one, as a bit for each group.
+fn differences(groups: &[Vec<usize>], solution: &str, other: &str) -> u128 {
+    let (solution, other) = (solution.as_bytes(), other.as_bytes());
+    groups
+        .iter()
+        .enumerate()
+        .filter(|(_, group)| group.iter().any(|&index| solution[index] != other[index]))
+        .fold(0, |set, (group, _)| set | 1 << group)
+}
+
+/// Returns a smallest set of groups that hits every set and has fewer groups than `best`, trying sizes
+/// from `min_size` up. Returns `Some(None)` if there is no such set, and `None` if the search takes
+/// more than `MAX_BRANCHES` branches.
+fn smallest_hitting_set(sets: &[u128], min_size: usize, best: u128) -> Option<Option<u128>> {
+    let mut branches = 0;
+    for size in min_size..best.count_ones() as usize {
+        if let Some(chosen) = hit(sets, 0, size, &mut branches)? {
+            return Some(Some(chosen));
+        }
+    }
+    Some(None)
+}
+
+/// Searches for at most `size` more groups that together with `chosen` hit every set. Returns `None` if
+/// the search runs out of branches, and `Some(None)` if there is no such set.
+fn hit(sets: &[u128], chosen: u128, size: usize, branches: &mut usize) -> Option<Option<u128>> {
+    *branches += 1;
+    if MAX_BRANCHES < *branches {
+        return None;
+    }
+    let missed: Vec<u128> = sets
+        .iter()
+        .copied()
+        .filter(|set| 0 == set & chosen)
+        .collect();
+    let smallest = match missed.iter().min_by_key(|set| set.count_ones()) {
+        Some(&smallest) => smallest,
+        None => return Some(Some(chosen)),
+    };
+    if size < lower_bound(&missed) {
+        return Some(None);
+    }
+    let mut options = smallest;
+    while 0 != options {
+        let group = options & options.wrapping_neg();
+        options &= !group;
+        if let Some(found) = hit(&missed, chosen | group, size - 1, branches)? {
+            return Some(Some(found));
+        }
+    }
+    Some(None)
+}
+
+/// Returns the number of sets that share no group with each other, which is the least number of groups
+/// any hitting set needs.
+fn lower_bound(sets: &[u128]) -> usize {
+    let mut sets = sets.to_vec();
+    sets.sort_unstable_by_key(|set| set.count_ones());
+    let mut used = 0;
+    let mut count = 0;
+    for set in sets {
+        if 0 == set & used {
+            used |= set;
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::minimize::is_unique;
+
+    #[test]
+    fn adds_the_fewest_clues() {
+        let puzzle =
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+        let fix = fix_unique(puzzle, None).unwrap();
+        assert!(fix.is_minimum);
+        assert!(is_unique(&fix.puzzle));
+        // No smaller set of the kept solution's values makes the puzzle unique.
+        for skipped in 0..fix.clues.len() {
+            let mut fewer = fix.puzzle.clone();
+            fewer.replace_range(fix.clues[skipped]..=fix.clues[skipped], ".");
+            assert!(!is_unique(&fewer));
+        }
+        let unique = fix_unique(&fix.puzzle, None).unwrap();
+        assert!(unique.clues.is_empty());
+        assert_eq!(None, fix_unique("11", None));
+    }
+
+    #[test]
+    fn keeps_symmetry() {
+        let puzzle = SudokuBoard::from("123456789").to_string_puzzle();
+        let rotational = symmetry("rotational").unwrap();
+        let fix = fix_unique(&puzzle, Some(&rotational)).unwrap();
+        assert!(is_unique(&fix.puzzle));
+        for &clue in &fix.clues {
+            let mirror = rotational.source(clue);
+            assert!(fix.clues.contains(&mirror) || b'.' != puzzle.as_bytes()[mirror]);
+        }
+        assert!(symmetry("none").is_none());
+    }
+}
@@ -0,0 +1,403 @@
+//! Reading and writing puzzles in the file formats used by other sudoku tools.
+//! ```text
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  sdk     SadMan Sudoku: # comments, then a [Puzzle] section of 9 rows and an optional [State] section
+//!          holding the values filled in so far. One puzzle per file.
+//!  sdm     SadMan Sudoku multi-puzzle: one puzzle of 81 digits per line, with 0 for each empty square.
+//!  ss      Simple Sudoku: 9 rows of `.75|...|..4` with `---+---+---` between bands.
+//!  hodoku  HoDoKu: one puzzle of 81 squares per line, with `.` for each empty square and `+` before filled values.
+//!  csv     9 rows of 9 comma-separated values, with an empty value for each empty square.
+//!  txt     The input format of sudoku-cli, where any non-digit is an empty square: one puzzle of 81 squares per
+//!          line, or a grid of 9 rows of 9 squares.
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Only sdk and hodoku can tell givens from values filled in later. The other formats write every value as a given.
+//!  Puzzles in multi-line formats are separated by a blank line.
+//! ─────────────────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::SudokuBoard;
+use std::io;
+use std::path::Path;
+
+/// The names of every supported format, as accepted by [read](fn.read.html) and [write](fn.write.html).
+pub const FORMATS: [&str; 6] = ["sdk", "sdm", "ss", "hodoku", "csv", "txt"];
+
+/// A puzzle's givens along with every value on the board, including values filled in after the givens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    /// The givens as a puzzle string of 81 characters, with `.` for each empty square.
+    pub givens: String,
+    /// Every value on the board as a puzzle string of 81 characters, with `.` for each empty square.
+    pub values: String,
+}
+
+impl Grid {
+    /// Creates a grid with only givens.
+    pub fn new(givens: &str) -> Self {
+        Grid::with_values(givens, givens)
+    }
+
+    /// Creates a grid with givens and values filled in after them. Givens always take priority over values.
+    pub fn with_values(givens: &str, values: &str) -> Self {
+        let givens = SudokuBoard::from(givens).to_string_puzzle();
+        let values = givens
+            .chars()
+            .zip(SudokuBoard::from(values).to_string_puzzle().chars())
+            .map(|(given, value)| if '.' == given { value } else { given })
+            .collect();
+        Grid { givens, values }
+    }
+
+    /// Returns true if any value was filled in after the givens.
+    pub fn has_filled_values(&self) -> bool {
+        self.givens != self.values
+    }
+
+    /// Returns the value of each square along with whether it was filled in after the givens.
+    fn squares(&self) -> impl Iterator<Item = (char, bool)> + '_ {
+        self.givens
+            .chars()
+            .zip(self.values.chars())
+            .map(|(given, value)| (value, '.' == given && '.' != value))
+    }
+}
+
+/// Returns the format implied by a file's extension, if it has one of the supported formats.
+pub fn detect(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+    FORMATS
+        .iter()
+        .find(|&&format| format == extension && "hodoku" != format)
+        .copied()
+}
+
+/// Reads every puzzle from text in the given format.
+pub fn read(format: &str, text: &str) -> io::Result<Vec<Grid>> {
+    let grids = match format {
+        "sdk" => vec![read_sdk(text)?],
+        "sdm" => lines(text)
+            .map(|line| Ok(Grid::new(&parse_squares(line, ".0")?)))
+            .collect::<io::Result<_>>()?,
+        "txt" => blocks(text)
+            .iter()
+            .map(|block| read_txt(block))
+            .collect::<io::Result<Vec<_>>>()?
+            .concat(),
+        "ss" => blocks(text)
+            .iter()
+            .map(|block| Ok(Grid::new(&parse_squares(block, ".0xX|-+*")?)))
+            .collect::<io::Result<_>>()?,
+        "hodoku" => lines(text).map(read_hodoku).collect::<io::Result<_>>()?,
+        "csv" => blocks(text)
+            .iter()
+            .map(|block| read_csv(block))
+            .collect::<io::Result<_>>()?,
+        _ => return Err(invalid(&format!("unknown format: {}", format))),
+    };
+    if grids.is_empty() {
+        return Err(invalid("the input does not contain a puzzle"));
+    }
+    Ok(grids)
+}
+
+/// Writes every puzzle as text in the given format.
+pub fn write(format: &str, grids: &[Grid]) -> io::Result<String> {
+    let texts: Vec<String> = match format {
+        "sdk" if 1 < grids.len() => return Err(invalid("sdk files hold a single puzzle")),
+        "sdk" => grids.iter().map(write_sdk).collect(),
+        "sdm" => grids
+            .iter()
+            .map(|grid| grid.values.replace('.', "0") + "\n")
+            .collect(),
+        "txt" => grids
+            .iter()
+            .map(|grid| grid.values.clone() + "\n")
+            .collect(),
+        "ss" => grids.iter().map(write_ss).collect(),
+        "hodoku" => grids.iter().map(write_hodoku).collect(),
+        "csv" => grids.iter().map(write_csv).collect(),
+        _ => return Err(invalid(&format!("unknown format: {}", format))),
+    };
+    let separator = match format {
+        "ss" | "csv" => "\n",
+        _ => "",
+    };
+    Ok(texts.join(separator))
+}
+
+/// Returns true if the format can tell givens from values filled in later.
+pub fn keeps_filled_values(format: &str) -> bool {
+    "sdk" == format || "hodoku" == format
+}
+
+fn read_sdk(text: &str) -> io::Result<Grid> {
+    let mut sections: Vec<(String, String)> = vec![(String::new(), String::new())];
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            sections.push((line.to_lowercase(), String::new()));
+        } else if let Some((_, rows)) = sections.last_mut() {
+            rows.push_str(line);
+        }
+    }
+    let section = |name: &str| {
+        sections
+            .iter()
+            .find(|(header, rows)| header == name && !rows.is_empty())
+            .map(|(_, rows)| parse_squares(rows, ".0"))
+            .transpose()
+    };
+    let givens = match section("[puzzle]")? {
+        Some(givens) => givens,
+        None => parse_squares(&sections[0].1, ".0")?,
+    };
+    let values = section("[state]")?.unwrap_or_else(|| givens.clone());
+    Ok(Grid::with_values(&givens, &values))
+}
+
+fn write_sdk(grid: &Grid) -> String {
+    let mut text = String::from("[Puzzle]\n");
+    text += &rows(&grid.givens).join("\n");
+    text.push('\n');
+    if grid.has_filled_values() {
+        text += "[State]\n";
+        text += &rows(&grid.values).join("\n");
+        text.push('\n');
+    }
+    text
+}
+
+fn write_ss(grid: &Grid) -> String {
+    let mut text = String::new();
+    for (index, row) in rows(&grid.values).iter().enumerate() {
+        if 0 < index && 0 == index % 3 {
+            text += "---+---+---\n";
+        }
+        text += &format!("{}|{}|{}\n", &row[0..3], &row[3..6], &row[6..9]);
+    }
+    text
+}
+
+/// Reads a block of lines that each hold a puzzle of 81 squares, or that together hold a single grid.
+fn read_txt(block: &str) -> io::Result<Vec<Grid>> {
+    let squares = |text: &str| -> String { text.chars().filter(|c| !c.is_whitespace()).collect() };
+    let puzzles: Vec<String> = block.lines().map(squares).collect();
+    if puzzles.iter().all(|puzzle| 81 == puzzle.chars().count()) {
+        return Ok(puzzles.iter().map(|puzzle| Grid::new(puzzle)).collect());
+    }
+    let grid = squares(block);
+    check_length(&grid, block)?;
+    Ok(vec![Grid::new(&grid)])
+}
+
+fn read_hodoku(line: &str) -> io::Result<Grid> {
+    let (mut givens, mut values) = (String::new(), String::new());
+    let mut is_filled = false;
+    for c in line.chars().filter(|c| !c.is_whitespace()) {
+        match c {
+            '+' => is_filled = true,
+            '1'..='9' if is_filled => {
+                givens.push('.');
+                values.push(c);
+                is_filled = false;
+            }
+            '1'..='9' | '.' | '0' => {
+                let c = if '0' == c { '.' } else { c };
+                givens.push(c);
+                values.push(c);
+            }
+            _ => {
+                return Err(invalid(&format!(
+                    "unexpected character {:?} in: {}",
+                    c, line
+                )))
+            }
+        }
+    }
+    check_length(&values, line)?;
+    Ok(Grid::with_values(&givens, &values))
+}
+
+fn write_hodoku(grid: &Grid) -> String {
+    let mut line = String::new();
+    for (value, is_filled) in grid.squares() {
+        if is_filled {
+            line.push('+');
+        }
+        line.push(value);
+    }
+    line + "\n"
+}
+
+fn read_csv(block: &str) -> io::Result<Grid> {
+    let mut squares = String::new();
+    for row in block.lines() {
+        let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+        if 9 != fields.len() {
+            return Err(invalid(&format!("expected 9 values in row: {}", row)));
+        }
+        for field in fields {
+            match field {
+                "" | "0" | "." => squares.push('.'),
+                _ if 1 == field.len()
+                    && ('1'..='9').contains(&field.chars().next().unwrap_or('0')) =>
+                {
+                    squares.push_str(field)
+                }
+                _ => {
+                    return Err(invalid(&format!(
+                        "unexpected value {:?} in row: {}",
+                        field, row
+                    )))
+                }
+            }
+        }
+    }
+    check_length(&squares, block)?;
+    Ok(Grid::new(&squares))
+}
+
+fn write_csv(grid: &Grid) -> String {
+    rows(&grid.values)
+        .iter()
+        .map(|row| {
+            let fields: Vec<String> = row
+                .chars()
+                .map(|c| c.to_string().replace('.', ""))
+                .collect();
+            fields.join(",") + "\n"
+        })
+        .collect()
+}
+
+/// Reads the squares of a puzzle, treating any of `blanks` as an empty square or, for `|-+*`, as decoration.
+fn parse_squares(text: &str, blanks: &str) -> io::Result<String> {
+    let mut squares = String::new();
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        match c {
+            '1'..='9' => squares.push(c),
+            '|' | '-' | '+' | '*' if blanks.contains(c) => {}
+            _ if blanks.contains(c) => squares.push('.'),
+            _ => {
+                return Err(invalid(&format!(
+                    "unexpected character {:?} in: {}",
+                    c, text
+                )))
+            }
+        }
+    }
+    check_length(&squares, text)?;
+    Ok(squares)
+}
+
+/// Returns an error unless a puzzle has exactly 81 squares.
+fn check_length(squares: &str, text: &str) -> io::Result<()> {
+    if 81 != squares.chars().count() {
+        return Err(invalid(&format!(
+            "expected 81 squares, found {} in: {}",
+            squares.chars().count(),
+            text
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the non-blank lines of the text that are not comments.
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Returns each group of lines separated by blank lines, skipping comments.
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = vec![String::new()];
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            blocks.push(String::new());
+        } else if let Some(block) = blocks.last_mut() {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+    blocks
+}
+
+/// Splits a puzzle string into its 9 rows.
+fn rows(puzzle: &str) -> Vec<&str> {
+    (0..9).map(|row| &puzzle[row * 9..row * 9 + 9]).collect()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIVENS: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+    const VALUES: &str =
+        "975.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn round_trips() {
+        let grid = Grid::with_values(GIVENS, VALUES);
+        assert!(grid.has_filled_values());
+        for format in FORMATS.iter() {
+            let text = write(format, std::slice::from_ref(&grid)).unwrap();
+            let read = read(format, &text).unwrap();
+            assert_eq!(1, read.len(), "format: {}", format);
+            assert_eq!(grid.values, read[0].values, "format: {}", format);
+            if keeps_filled_values(format) {
+                assert_eq!(grid, read[0], "format: {}", format);
+            } else {
+                assert_eq!(grid.values, read[0].givens, "format: {}", format);
+            }
+        }
+        let many = vec![Grid::new(GIVENS), Grid::new(VALUES)];
+        for format in FORMATS.iter().filter(|&&format| "sdk" != format) {
+            let text = write(format, &many).unwrap();
+            assert_eq!(many, read(format, &text).unwrap(), "format: {}", format);
+        }
+        assert!(write("sdk", &many).is_err());
+    }
+
+    #[test]
+    fn reads_other_tools() {
+        let sdk =
+            "#A Someone\n#D An easy one\n[Puzzle]\n.75.....4\n2139.5.7.\n..8.7...9\n..2417...\n\
+                   4...6...1\n...8324..\n3...9.7..\n.5.3.4698\n8.....31.\n";
+        assert_eq!(vec![Grid::new(GIVENS)], read("sdk", sdk).unwrap());
+        let ss = "*-----------*\n|.75|...|..4|\n|213|9.5|.7.|\n|..8|.7.|..9|\n|---+---+---|\n\
+                  |..2|417|...|\n|4..|.6.|..1|\n|...|832|4..|\n|---+---+---|\n|3..|.9.|7..|\n\
+                  |.5.|3.4|698|\n|8..|...|31.|\n*-----------*\n";
+        assert_eq!(vec![Grid::new(GIVENS)], read("ss", ss).unwrap());
+        let hodoku = format!("+9{}\n", &GIVENS[1..]);
+        assert_eq!(
+            vec![Grid::with_values(GIVENS, VALUES)],
+            read("hodoku", &hodoku).unwrap()
+        );
+        assert!(read("sdm", "123").is_err());
+        assert!(read("csv", "1,2,3\n").is_err());
+        assert!(read("txt", "").is_err());
+        let grid = "- 7 5 - - - - - 4\n2 1 3 9 - 5 - 7 -\n- - 8 - 7 - - - 9\n- - 2 4 1 7 - - -\n\
+                    4 - - - 6 - - - 1\n- - - 8 3 2 4 - -\n3 - - - 9 - 7 - -\n- 5 - 3 - 4 6 9 8\n\
+                    8 - - - - - 3 1 -\n";
+        let three = format!("{}\n{}\n\n{}", grid, VALUES, grid);
+        assert_eq!(
+            vec![Grid::new(GIVENS), Grid::new(VALUES), Grid::new(GIVENS)],
+            read("txt", &three).unwrap()
+        );
+        assert!(read("txt", &grid[..grid.len() - 20]).is_err());
+        assert_eq!(Some("sdk"), detect("puzzles/easy.SDK"));
+        assert_eq!(None, detect("puzzles/easy"));
+    }
+}
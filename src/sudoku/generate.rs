@@ -0,0 +1,71 @@
+//! Generates new puzzles with a unique solution from a seed.
+//! A random solution grid is found first, then clues are removed in a random order while the solution stays unique,
+//! so every generated puzzle is minimal.
+
+use crate::sudoku::board::{SearchEvent, SudokuBoard};
+use crate::sudoku::heuristic::MinimumRemainingValuesRandom;
+use crate::sudoku::minimize;
+use crate::sudoku::random::mix;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Returns a random, completely filled grid that depends only on the seed.
+pub fn solution_grid(seed: u64) -> String {
+    solution_grid_cancellable(seed, &Arc::default())
+}
+
+/// Returns a random, completely filled grid like [solution_grid](fn.solution_grid.html),
+/// stopping early once `cancel` is set, after which the grid is not meaningful.
+fn solution_grid_cancellable(seed: u64, cancel: &Arc<AtomicBool>) -> String {
+    let heuristic = Arc::new(MinimumRemainingValuesRandom::new(seed));
+    let mut board = SudokuBoard::from("")
+        .with_heuristic(heuristic)
+        .with_cancel(Arc::clone(cancel));
+    let mut grid = String::new();
+    board.search_observed(false, &mut |board, event| {
+        if let SearchEvent::Solution { .. } = event {
+            grid = board.to_string_puzzle();
+        }
+    });
+    grid
+}
+
+/// Returns a minimal puzzle with a unique solution along with that solution, both depending only on the seed.
+pub fn generate(seed: u64) -> (String, String) {
+    generate_cancellable(seed, &Arc::default()).expect("a full grid has a unique solution")
+}
+
+/// Generates a puzzle like [generate](fn.generate.html), stopping early once `cancel` is set.  
+/// Returns `None` if the search was cancelled before the puzzle was finished.
+pub fn generate_cancellable(seed: u64, cancel: &Arc<AtomicBool>) -> Option<(String, String)> {
+    let solution = solution_grid_cancellable(seed, cancel);
+    let order = minimize::clue_order(&solution, "random", mix(&[seed, 1]))
+        .expect("random is one of CLUE_ORDERS");
+    let puzzle = minimize::minimize_cancellable(&solution, &order, cancel)?;
+    if cancel.load(Ordering::Relaxed) {
+        return None;
+    }
+    Some((puzzle, solution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::Uniqueness;
+
+    #[test]
+    fn reproducible() {
+        let (puzzle, solution) = generate(3);
+        assert_eq!((puzzle.clone(), solution.clone()), generate(3));
+        assert_ne!(solution, solution_grid(4));
+        assert_eq!(
+            None,
+            generate_cancellable(3, &Arc::new(AtomicBool::new(true)))
+        );
+        assert!(!solution.contains('.'));
+        assert_eq!(
+            Uniqueness::Unique(solution),
+            SudokuBoard::from(puzzle.as_str()).check_unique()
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Grades the difficulty of a puzzle with a unique solution by how much guessing the search needs.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  A guess is a square the search fills while it still has more than one option.
+//!  The search always fills squares with a single option first, so a puzzle that needs no guesses
+//!  can be solved with naked singles alone.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  easy      no guesses
+//!  medium    up to 10 guesses
+//!  hard      up to 100 guesses
+//!  expert    more than 100 guesses
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::board::{SearchEvent, SudokuBoard};
+use crate::sudoku::minimize;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// The name of every grade from easiest to hardest.
+pub const GRADES: [&str; 4] = ["easy", "medium", "hard", "expert"];
+
+/// The difficulty of a puzzle along with the size of the search that proved its solution unique.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Grade {
+    /// One of [GRADES](constant.GRADES.html).
+    pub level: &'static str,
+    /// The number of squares filled while they had more than one option.
+    pub guesses: usize,
+    /// The number of squares filled in total.
+    pub fills: usize,
+}
+
+/// Grades a puzzle, or returns `None` if it does not have a unique solution.
+pub fn grade(puzzle: &str) -> Option<Grade> {
+    grade_cancellable(puzzle, &Arc::default())
+}
+
+/// Grades a puzzle like [grade](fn.grade.html), stopping early once `cancel` is set,
+/// after which the grade is not meaningful.
+pub fn grade_cancellable(puzzle: &str, cancel: &Arc<AtomicBool>) -> Option<Grade> {
+    if !minimize::is_unique_cancellable(puzzle, cancel) {
+        return None;
+    }
+    let (mut guesses, mut fills) = (0, 0);
+    let mut options = Vec::new();
+    SudokuBoard::from(puzzle)
+        .with_cancel(Arc::clone(cancel))
+        .search_observed(true, &mut |_, event| match event {
+            SearchEvent::Branch {
+                options: count,
+                depth,
+                ..
+            } => {
+                options.truncate(depth);
+                options.push(count);
+            }
+            SearchEvent::Fill { depth, .. } => {
+                fills += 1;
+                if 1 < options[depth - 1] {
+                    guesses += 1;
+                }
+            }
+            _ => {}
+        });
+    let level = match guesses {
+        0 => GRADES[0],
+        1..=10 => GRADES[1],
+        11..=100 => GRADES[2],
+        _ => GRADES[3],
+    };
+    Some(Grade {
+        level,
+        guesses,
+        fills,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grades() {
+        let easy =
+            ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+        let grade = grade(easy).unwrap();
+        assert_eq!("easy", grade.level);
+        assert_eq!(0, grade.guesses);
+        assert_eq!(easy.matches('.').count(), grade.fills);
+        let hard =
+            "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9";
+        assert!(super::grade(hard).unwrap().guesses > 0);
+        let multiple =
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+        assert_eq!(None, super::grade(multiple));
+        let cancelled = Arc::new(AtomicBool::new(true));
+        assert_eq!(None, grade_cancellable(hard, &cancelled));
+    }
+}
@@ -0,0 +1,298 @@
+//! Branching heuristics that decide which square the search fills next and in what order it tries values.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  mrv         the square with the fewest options, the first one found wins ties; values in order 1..=9
+//!  mrv-degree  the square with the fewest options, ties go to the square with the most empty peers
+//!  lcv         squares as mrv; values that remove the fewest options from empty peers are tried first
+//!  mrv-random  the square with the fewest options, a random one wins ties; values in a random order
+//!  random      a random square and a random order of values, reproducible with a seed
+//!  row-major   the first empty square from left to right, top to bottom; values in order 1..=9
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Every heuristic backtracks as soon as any empty square has no options left.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::board::{SudokuBoard, SudokuSquare};
+use crate::sudoku::random::{mix, SplitMix64};
+use std::fmt;
+use std::sync::Arc;
+
+/// The names of every built-in heuristic, as accepted by [by_name](fn.by_name.html).
+pub const HEURISTICS: [&str; 6] = [
+    "mrv",
+    "mrv-degree",
+    "mrv-random",
+    "lcv",
+    "random",
+    "row-major",
+];
+
+/// A strategy for choosing the next square to fill and the order in which to try its values.
+pub trait Heuristic: fmt::Debug + Send + Sync {
+    /// Returns the index of the next square to fill from a non-empty slice of the board's empty squares.
+    /// Returning `None` means some square has no options left, so the search backtracks immediately.
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize>;
+
+    /// Reorders the values available for a square, which are given in ascending order.
+    /// This is only called when a square has more than one option.
+    fn order_values(&self, _board: &SudokuBoard, _square: &SudokuSquare, _values: &mut [usize]) {}
+}
+
+/// Returns true if the heuristic makes random choices, so that its seed decides how the search goes.
+pub fn is_randomized(name: &str) -> bool {
+    "mrv-random" == name || "random" == name
+}
+
+/// Returns the built-in heuristic with the given name, seeding it if it is randomized.
+pub fn by_name(name: &str, seed: u64) -> Option<Arc<dyn Heuristic>> {
+    match name {
+        "mrv" => Some(Arc::new(MinimumRemainingValues)),
+        "mrv-degree" => Some(Arc::new(MinimumRemainingValuesDegree)),
+        "mrv-random" => Some(Arc::new(MinimumRemainingValuesRandom::new(seed))),
+        "lcv" => Some(Arc::new(LeastConstrainingValue)),
+        "random" => Some(Arc::new(Random::new(seed))),
+        "row-major" => Some(Arc::new(RowMajor)),
+        _ => None,
+    }
+}
+
+/// Chooses the square with the fewest options. The first square found wins ties.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinimumRemainingValues;
+
+impl Heuristic for MinimumRemainingValues {
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize> {
+        let mut index = 0;
+        let mut min_options = board.count_options(&squares[index]);
+        for i in (1..squares.len()).rev() {
+            match min_options {
+                0 => return None,
+                1 => return Some(index),
+                _ => {
+                    let curr_options = board.count_options(&squares[i]);
+                    if curr_options < min_options {
+                        min_options = curr_options;
+                        index = i;
+                    }
+                }
+            }
+        }
+        Some(index)
+    }
+}
+
+/// Chooses the square with the fewest options, breaking ties by the most empty peers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinimumRemainingValuesDegree;
+
+impl Heuristic for MinimumRemainingValuesDegree {
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize> {
+        let mut best = None;
+        for (i, square) in squares.iter().enumerate() {
+            let options = board.count_options(square);
+            if 0 == options {
+                return None;
+            }
+            match best {
+                Some((_, min_options, _)) if options > min_options => {}
+                Some((_, min_options, max_degree)) if options == min_options => {
+                    let curr_degree = degree(square, squares);
+                    if curr_degree > max_degree {
+                        best = Some((i, options, curr_degree));
+                    }
+                }
+                _ => best = Some((i, options, degree(square, squares))),
+            }
+        }
+        best.map(|(index, _, _)| index)
+    }
+}
+
+/// Chooses the square with the fewest options, breaking ties at random, and tries its values in a random order.
+/// The choices depend only on the seed and the state of the board, so they do not depend on thread scheduling.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinimumRemainingValuesRandom {
+    seed: u64,
+}
+
+impl MinimumRemainingValuesRandom {
+    /// Creates a randomized minimum remaining values heuristic from a seed.
+    pub fn new(seed: u64) -> Self {
+        MinimumRemainingValuesRandom { seed }
+    }
+}
+
+impl Heuristic for MinimumRemainingValuesRandom {
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize> {
+        let mut ties = Vec::with_capacity(squares.len());
+        let mut min_options = 10;
+        for (i, square) in squares.iter().enumerate() {
+            let options = board.count_options(square);
+            match options {
+                0 => return None,
+                // A square with one option must be filled with it in every solution, so it never needs a tiebreak.
+                1 => return Some(i),
+                _ if options < min_options => {
+                    min_options = options;
+                    ties.clear();
+                    ties.push(i);
+                }
+                _ if options == min_options => ties.push(i),
+                _ => {}
+            }
+        }
+        let mut rng = SplitMix64::new(mix(&[self.seed, board.state_hash()]));
+        Some(ties[rng.below(ties.len())])
+    }
+
+    fn order_values(&self, board: &SudokuBoard, square: &SudokuSquare, values: &mut [usize]) {
+        Random::new(self.seed).order_values(board, square, values);
+    }
+}
+
+/// Chooses squares as [MinimumRemainingValues](struct.MinimumRemainingValues.html) does,
+/// but tries the values that remove the fewest options from empty peers first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LeastConstrainingValue;
+
+impl Heuristic for LeastConstrainingValue {
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize> {
+        MinimumRemainingValues.select_square(board, squares)
+    }
+
+    fn order_values(&self, board: &SudokuBoard, square: &SudokuSquare, values: &mut [usize]) {
+        let empty_peers: Vec<SudokuSquare> = peers(square)
+            .filter(|peer| 0 == board.value_at(peer))
+            .collect();
+        values.sort_by_key(|&value| {
+            empty_peers
+                .iter()
+                .filter(|peer| 0 == board.options(peer) & as_bit(value))
+                .count()
+        });
+    }
+}
+
+/// Chooses a random square and tries its values in a random order.
+/// The choices depend only on the seed and the state of the board, so they do not depend on thread scheduling.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Random {
+    seed: u64,
+}
+
+impl Random {
+    /// Creates a random heuristic from a seed.
+    pub fn new(seed: u64) -> Self {
+        Random { seed }
+    }
+}
+
+impl Heuristic for Random {
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize> {
+        if is_dead_end(board, squares) {
+            return None;
+        }
+        let mut rng = SplitMix64::new(mix(&[self.seed, board.state_hash()]));
+        Some(rng.below(squares.len()))
+    }
+
+    fn order_values(&self, board: &SudokuBoard, square: &SudokuSquare, values: &mut [usize]) {
+        let position = (square.row() * 9 + square.col()) as u64;
+        SplitMix64::new(mix(&[self.seed, board.state_hash(), position])).shuffle(values);
+    }
+}
+
+/// Chooses the first empty square from left to right, top to bottom.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RowMajor;
+
+impl Heuristic for RowMajor {
+    fn select_square(&self, board: &SudokuBoard, squares: &[SudokuSquare]) -> Option<usize> {
+        if is_dead_end(board, squares) {
+            return None;
+        }
+        squares
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, square)| (square.row(), square.col()))
+            .map(|(index, _)| index)
+    }
+}
+
+/// Returns true if any of the squares has no options left.
+fn is_dead_end(board: &SudokuBoard, squares: &[SudokuSquare]) -> bool {
+    squares
+        .iter()
+        .any(|square| 0 == board.count_options(square))
+}
+
+/// Returns the number of other squares in the slice that share a row, column, or box with this square.
+fn degree(square: &SudokuSquare, squares: &[SudokuSquare]) -> usize {
+    squares
+        .iter()
+        .filter(|other| is_peer(square, other))
+        .count()
+}
+
+/// Returns true if two different squares share a row, column, or box.
+fn is_peer(a: &SudokuSquare, b: &SudokuSquare) -> bool {
+    (a.row() != b.row() || a.col() != b.col())
+        && (a.row() == b.row() || a.col() == b.col() || a.bx() == b.bx())
+}
+
+/// Returns an iterator over the 20 squares that share a row, column, or box with this square.
+fn peers(square: &SudokuSquare) -> impl Iterator<Item = SudokuSquare> + '_ {
+    (0..9)
+        .flat_map(|row| (0..9).map(move |col| SudokuSquare::at(row, col)))
+        .filter(move |other| is_peer(square, other))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+
+    #[test]
+    fn every_heuristic_counts_the_same() {
+        for name in HEURISTICS.iter() {
+            let heuristic = by_name(name, 42).unwrap();
+            let mut board = SudokuBoard::from(PUZZLE).with_heuristic(heuristic);
+            assert_eq!(35, board.count_solutions(), "heuristic: {}", name);
+            let (count, _) = board.find_one_compact();
+            assert_eq!(1, count, "heuristic: {}", name);
+        }
+        assert!(by_name("unknown", 0).is_none());
+    }
+
+    #[test]
+    fn random_solutions() {
+        let puzzle =
+            ".75.....4.1...5.7...8.7...9..2417...4.......1...8.24..3...9.7...5.3.4..88.....31.";
+        let find_one = |seed| {
+            let heuristic = by_name("mrv-random", seed).unwrap();
+            SudokuBoard::from(puzzle)
+                .with_heuristic(heuristic)
+                .find_one_compact()
+        };
+        let mut distinct = Vec::new();
+        for seed in 0..20 {
+            let (count, solution) = find_one(seed);
+            assert_eq!(1, count);
+            assert_eq!(solution, find_one(seed).1);
+            if !distinct.contains(&solution) {
+                distinct.push(solution);
+            }
+        }
+        assert!(distinct.len() > 1);
+    }
+
+    #[test]
+    fn peers() {
+        let square = SudokuSquare::at(4, 4);
+        assert_eq!(20, super::peers(&square).count());
+        assert!(super::peers(&square).all(|peer| is_peer(&square, &peer)));
+    }
+}
@@ -0,0 +1,130 @@
+//! Reduces a puzzle with a unique solution to a minimal set of clues, where every remaining clue is necessary.
+//! Puzzles are strings of 81 characters as returned by
+//! [to_string_puzzle](../board/struct.SudokuBoard.html#method.to_string_puzzle), with `.` for each empty square.
+
+use crate::sudoku::board::{SudokuBoard, Uniqueness};
+use crate::sudoku::random::SplitMix64;
+use rayon::prelude::*;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// The names of every order in which clues can be removed, as accepted by [clue_order](fn.clue_order.html).
+pub const CLUE_ORDERS: [&str; 3] = ["row-major", "reverse", "random"];
+
+/// Returns true if the puzzle has exactly one solution.
+pub fn is_unique(puzzle: &str) -> bool {
+    is_unique_cancellable(puzzle, &Arc::default())
+}
+
+/// Returns true if the puzzle has exactly one solution, stopping early once `cancel` is set,
+/// after which the answer is not meaningful.
+pub fn is_unique_cancellable(puzzle: &str, cancel: &Arc<AtomicBool>) -> bool {
+    match SudokuBoard::from(puzzle)
+        .with_cancel(Arc::clone(cancel))
+        .check_unique()
+    {
+        Uniqueness::Unique(_) => true,
+        Uniqueness::Unsolvable | Uniqueness::Multiple(_, _) => false,
+    }
+}
+
+/// Returns the index `(row * 9 + col)` of every clue in the puzzle.
+pub fn clues(puzzle: &str) -> Vec<usize> {
+    puzzle
+        .char_indices()
+        .filter(|&(_, c)| c != '.')
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Returns the puzzle with the clue at the given index replaced by an empty square.
+pub fn without_clue(puzzle: &str, index: usize) -> String {
+    puzzle
+        .char_indices()
+        .map(|(i, c)| if i == index { '.' } else { c })
+        .collect()
+}
+
+/// Returns the clues of the puzzle in the order they should be tried for removal.
+pub fn clue_order(puzzle: &str, order: &str, seed: u64) -> Option<Vec<usize>> {
+    let mut clues = clues(puzzle);
+    match order {
+        "row-major" => {}
+        "reverse" => clues.reverse(),
+        "random" => SplitMix64::new(seed).shuffle(&mut clues),
+        _ => return None,
+    }
+    Some(clues)
+}
+
+/// Removes clues one at a time in the given order, keeping each removal that leaves the solution unique.
+/// Returns the minimal puzzle, or `None` if the puzzle does not have a unique solution.
+pub fn minimize(puzzle: &str, order: &[usize]) -> Option<String> {
+    minimize_cancellable(puzzle, order, &Arc::default())
+}
+
+/// Minimizes a puzzle like [minimize](fn.minimize.html), stopping early once `cancel` is set,
+/// after which the puzzle returned is not meaningful.
+pub fn minimize_cancellable(
+    puzzle: &str,
+    order: &[usize],
+    cancel: &Arc<AtomicBool>,
+) -> Option<String> {
+    if !is_unique_cancellable(puzzle, cancel) {
+        return None;
+    }
+    let mut minimal = String::from(puzzle);
+    for &index in order {
+        let candidate = without_clue(&minimal, index);
+        if is_unique_cancellable(&candidate, cancel) {
+            minimal = candidate;
+        }
+    }
+    Some(minimal)
+}
+
+/// Returns the index of every clue that could be removed on its own without losing a unique solution.
+/// Returns `None` if the puzzle does not have a unique solution.
+pub fn redundant_clues(puzzle: &str) -> Option<Vec<usize>> {
+    if !is_unique(puzzle) {
+        return None;
+    }
+    let mut redundant: Vec<usize> = clues(puzzle)
+        .into_par_iter()
+        .filter(|&index| is_unique(&without_clue(puzzle, index)))
+        .collect();
+    redundant.sort_unstable();
+    Some(redundant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn minimal() {
+        for order in CLUE_ORDERS.iter() {
+            let order = clue_order(PUZZLE, order, 7).unwrap();
+            let minimal = minimize(PUZZLE, &order).unwrap();
+            assert!(clues(&minimal).len() < clues(PUZZLE).len());
+            assert!(is_unique(&minimal));
+            assert_eq!(Some(vec![]), redundant_clues(&minimal));
+        }
+    }
+
+    #[test]
+    fn redundant() {
+        let redundant = redundant_clues(PUZZLE).unwrap();
+        assert!(!redundant.is_empty());
+        for index in redundant {
+            assert!(is_unique(&without_clue(PUZZLE, index)));
+        }
+        let multiple =
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+        assert_eq!(None, redundant_clues(multiple));
+        assert_eq!(None, minimize(multiple, &clues(multiple)));
+    }
+}
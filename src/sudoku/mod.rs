@@ -1,3 +1,28 @@
+pub mod analyze;
 pub mod bitwise;
 pub mod board;
+pub mod booklet;
+pub mod candidates;
+pub mod canon;
+pub mod checkpoint;
 pub mod data;
+pub mod estimate;
+pub mod explain;
+pub mod fix;
+pub mod formats;
+pub mod generate;
+pub mod grade;
+pub mod heuristic;
+pub mod memo;
+pub mod minimize;
+pub mod pdf;
+pub mod random;
+pub mod raster;
+pub mod recognize;
+pub mod render;
+pub mod sample;
+pub mod shard;
+pub mod trace;
+pub mod transform;
+pub mod tree;
+pub mod verify;
@@ -0,0 +1,252 @@
+//! A minimal PDF writer for pages of lines and text in the standard Helvetica fonts, which every reader has built in.
+//! Positions are in points (1/72 of an inch) from the top left corner of the page.
+//! Text is written in the WinAnsi encoding of those fonts, which covers Latin-1 and a few typographic marks.
+
+use std::fmt::Write;
+
+/// The two fonts available on every page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "/F1",
+            Font::Bold => "/F2",
+        }
+    }
+}
+
+/// The characters that WinAnsi places at 0x80 to 0x9F, where Latin-1 has control codes.
+/// Unassigned positions are `'\0'`.
+const WIN_ANSI_HIGH: [char; 32] = [
+    '€', '\0', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\0', 'Ž', '\0', '\0', '‘',
+    '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\0', 'ž', 'Ÿ',
+];
+
+/// Returns the WinAnsi code of a printable character, or `None` if the fonts cannot draw it.
+fn win_ansi(c: char) -> Option<u8> {
+    match c as u32 {
+        0x20..=0x7E | 0xA0..=0xFF => Some(c as u8),
+        _ => WIN_ANSI_HIGH
+            .iter()
+            .position(|&high| '\0' != high && high == c)
+            .map(|index| 0x80 + index as u8),
+    }
+}
+
+/// Returns true if every character of the text can be drawn by [Page::text](struct.Page.html#method.text).
+pub fn is_encodable(text: &str) -> bool {
+    text.chars().all(|c| win_ansi(c).is_some())
+}
+
+/// The drawing instructions of a single page.
+#[derive(Clone, Debug)]
+pub struct Page {
+    height: f64,
+    content: String,
+}
+
+impl Page {
+    /// Creates an empty page of the given height.
+    fn new(height: f64) -> Self {
+        Page {
+            height,
+            content: String::new(),
+        }
+    }
+
+    /// Draws a line between two points.
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S",
+            width,
+            from.0,
+            self.height - from.1,
+            to.0,
+            self.height - to.1
+        );
+    }
+
+    /// Draws the outline of a rectangle with its top left corner at `(x, y)`.
+    pub fn rect(&mut self, (x, y): (f64, f64), (width, height): (f64, f64), line_width: f64) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} w {:.2} {:.2} {:.2} {:.2} re S",
+            line_width,
+            x,
+            self.height - y - height,
+            width,
+            height
+        );
+    }
+
+    /// Sets the gray level of everything drawn afterwards, from 0 for black to 1 for white.
+    pub fn gray(&mut self, level: f64) {
+        let _ = writeln!(self.content, "{0:.2} G {0:.2} g", level);
+    }
+
+    /// Writes text with its baseline starting at `(x, y)`.  
+    /// Characters outside WinAnsi are drawn as `?`; check them first with [is_encodable](fn.is_encodable.html).
+    pub fn text(&mut self, (x, y): (f64, f64), size: f64, font: Font, text: &str) {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match win_ansi(c).unwrap_or(b'?') {
+                code @ (b'\\' | b'(' | b')') => {
+                    escaped.push('\\');
+                    escaped.push(code as char);
+                }
+                code @ 0x20..=0x7E => escaped.push(code as char),
+                code => {
+                    let _ = write!(escaped, "\\{:03o}", code);
+                }
+            }
+        }
+        let _ = writeln!(
+            self.content,
+            "BT {} {:.2} Tf {:.2} {:.2} Td ({}) Tj ET",
+            font.resource(),
+            size,
+            x,
+            self.height - y,
+            escaped
+        );
+    }
+}
+
+/// A document made of pages that all have the same size.
+#[derive(Clone, Debug)]
+pub struct Pdf {
+    width: f64,
+    height: f64,
+    pages: Vec<Page>,
+}
+
+impl Pdf {
+    /// Creates a document without pages, where every page will be `width` by `height` points.
+    pub fn new(width: f64, height: f64) -> Self {
+        Pdf {
+            width,
+            height,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Adds a blank page to the end of the document and returns it for drawing.
+    pub fn add_page(&mut self) -> &mut Page {
+        self.pages.push(Page::new(self.height));
+        self.pages.last_mut().expect("a page was just added")
+    }
+
+    /// Returns the number of pages.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns true if the document has no pages.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Writes the document in the PDF file format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Objects 1 to 4 are the catalog, the page tree, and the two fonts.
+        // Each page then takes two objects: the page itself and its content stream.
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|index| 5 + 2 * index).collect();
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        let mut objects = vec![
+            String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            ),
+            String::from(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+            ),
+            String::from(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+            ),
+        ];
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                self.width,
+                self.height,
+                id + 1
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                page.content.len(),
+                page.content
+            ));
+        }
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object);
+        }
+        let xref = pdf.len();
+        let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        pdf.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_structure() {
+        let mut pdf = Pdf::new(612.0, 792.0);
+        assert!(pdf.is_empty());
+        pdf.add_page()
+            .text((72.0, 72.0), 12.0, Font::Bold, "Puzzle (1)");
+        let page = pdf.add_page();
+        page.line((0.0, 0.0), (612.0, 792.0), 1.0);
+        page.rect((72.0, 72.0), (100.0, 100.0), 2.0);
+        assert_eq!(2, pdf.len());
+        let bytes = pdf.to_bytes();
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("(Puzzle \\(1\\)) Tj"));
+        let xref = text[text.find("startxref\n").unwrap() + 10..]
+            .lines()
+            .next()
+            .unwrap();
+        let xref: usize = xref.parse().unwrap();
+        assert!(text[xref..].starts_with("xref\n0 9\n"));
+        let first: usize = text[xref..].lines().nth(3).unwrap()[..10].parse().unwrap();
+        assert!(text[first..].starts_with("1 0 obj"));
+    }
+
+    #[test]
+    fn win_ansi_text() {
+        let mut pdf = Pdf::new(612.0, 792.0);
+        pdf.add_page()
+            .text((72.0, 72.0), 12.0, Font::Regular, "Rätsel – 5 € 数");
+        let text = String::from_utf8(pdf.to_bytes()).unwrap();
+        assert!(text.contains("(R\\344tsel \\226 5 \\200 ?) Tj"));
+        assert!(text.contains("/Encoding /WinAnsiEncoding"));
+        assert!(is_encodable("Café Œuvre “Sudoku”"));
+        assert!(!is_encodable("数独"));
+        assert!(!is_encodable("tab\t"));
+    }
+}
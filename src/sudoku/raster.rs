@@ -0,0 +1,240 @@
+//! A small anti-aliased RGB canvas and a built-in stroke font for drawing digits without any font files.
+//! Each digit is a set of polylines in a unit box, with `(0, 0)` at the top left and `(1, 1)` at the bottom right.
+
+/// A color as red, green, and blue.
+pub type Color = [u8; 3];
+
+/// The polylines that draw each digit from 1 to 9, at index `digit - 1`.
+const DIGIT_STROKES: [&[&[(f64, f64)]]; 9] = [
+    &[
+        &[(0.25, 0.22), (0.55, 0.0), (0.55, 1.0)],
+        &[(0.25, 1.0), (0.85, 1.0)],
+    ],
+    &[&[
+        (0.1, 0.25),
+        (0.3, 0.02),
+        (0.7, 0.02),
+        (0.9, 0.25),
+        (0.9, 0.4),
+        (0.1, 1.0),
+        (0.9, 1.0),
+    ]],
+    &[
+        &[
+            (0.1, 0.12),
+            (0.3, 0.0),
+            (0.72, 0.0),
+            (0.9, 0.15),
+            (0.9, 0.33),
+            (0.7, 0.48),
+            (0.4, 0.48),
+        ],
+        &[
+            (0.7, 0.48),
+            (0.9, 0.63),
+            (0.9, 0.85),
+            (0.72, 1.0),
+            (0.3, 1.0),
+            (0.1, 0.88),
+        ],
+    ],
+    &[&[(0.7, 1.0), (0.7, 0.0), (0.05, 0.7), (0.95, 0.7)]],
+    &[&[
+        (0.85, 0.0),
+        (0.2, 0.0),
+        (0.15, 0.45),
+        (0.65, 0.4),
+        (0.9, 0.6),
+        (0.9, 0.82),
+        (0.7, 1.0),
+        (0.3, 1.0),
+        (0.1, 0.88),
+    ]],
+    &[&[
+        (0.8, 0.05),
+        (0.55, 0.0),
+        (0.3, 0.1),
+        (0.12, 0.4),
+        (0.12, 0.8),
+        (0.3, 1.0),
+        (0.7, 1.0),
+        (0.9, 0.8),
+        (0.9, 0.62),
+        (0.7, 0.45),
+        (0.3, 0.45),
+        (0.12, 0.6),
+    ]],
+    &[&[(0.1, 0.0), (0.9, 0.0), (0.4, 1.0)]],
+    &[&[
+        (0.5, 0.47),
+        (0.2, 0.36),
+        (0.2, 0.12),
+        (0.35, 0.0),
+        (0.65, 0.0),
+        (0.8, 0.12),
+        (0.8, 0.36),
+        (0.5, 0.47),
+        (0.15, 0.6),
+        (0.15, 0.86),
+        (0.3, 1.0),
+        (0.7, 1.0),
+        (0.85, 0.86),
+        (0.85, 0.6),
+        (0.5, 0.47),
+    ]],
+    &[&[
+        (0.2, 0.95),
+        (0.45, 1.0),
+        (0.7, 0.9),
+        (0.88, 0.6),
+        (0.88, 0.2),
+        (0.7, 0.0),
+        (0.3, 0.0),
+        (0.1, 0.2),
+        (0.1, 0.38),
+        (0.3, 0.55),
+        (0.7, 0.55),
+        (0.88, 0.4),
+    ]],
+];
+
+/// An image of RGB pixels in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    /// Creates a canvas filled with one color.
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Returns the width of the canvas in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the canvas in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the color of a pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Returns the pixels as consecutive red, green, and blue bytes.
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| pixel.iter().copied())
+            .collect()
+    }
+
+    /// Mixes a color into a pixel, where `coverage` of 1 replaces the pixel and 0 leaves it unchanged.
+    fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f64) {
+        if x >= self.width || y >= self.height || coverage <= 0.0 {
+            return;
+        }
+        let coverage = coverage.min(1.0);
+        let pixel = &mut self.pixels[y * self.width + x];
+        for (channel, &target) in pixel.iter_mut().zip(color.iter()) {
+            *channel =
+                (*channel as f64 + (target as f64 - *channel as f64) * coverage).round() as u8;
+        }
+    }
+
+    /// Fills the rectangle from `(x0, y0)` to `(x1, y1)`, blending partially covered pixels at its edges.
+    pub fn fill_rect(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, color: Color) {
+        let (from_x, to_x) = (x0.floor().max(0.0) as usize, x1.ceil() as usize);
+        let (from_y, to_y) = (y0.floor().max(0.0) as usize, y1.ceil() as usize);
+        for y in from_y..to_y.min(self.height) {
+            let covered_y = (y1.min(y as f64 + 1.0) - y0.max(y as f64)).max(0.0);
+            for x in from_x..to_x.min(self.width) {
+                let covered_x = (x1.min(x as f64 + 1.0) - x0.max(x as f64)).max(0.0);
+                self.blend(x, y, color, covered_x * covered_y);
+            }
+        }
+    }
+
+    /// Draws a line with round ends between two points.
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color) {
+        let radius = width / 2.0;
+        let left = (from.0.min(to.0) - radius - 1.0).floor().max(0.0) as usize;
+        let right = (from.0.max(to.0) + radius + 1.0).ceil() as usize;
+        let top = (from.1.min(to.1) - radius - 1.0).floor().max(0.0) as usize;
+        let bottom = (from.1.max(to.1) + radius + 1.0).ceil() as usize;
+        for y in top..bottom.min(self.height) {
+            for x in left..right.min(self.width) {
+                let distance = distance_to_segment((x as f64 + 0.5, y as f64 + 0.5), from, to);
+                self.blend(x, y, color, radius + 0.5 - distance);
+            }
+        }
+    }
+
+    /// Draws a digit from the built-in stroke font inside the box with its top left corner at `(x, y)`.
+    pub fn digit(
+        &mut self,
+        digit: usize,
+        (x, y): (f64, f64),
+        (width, height): (f64, f64),
+        stroke: f64,
+        color: Color,
+    ) {
+        for polyline in digit_strokes(digit) {
+            for pair in polyline.windows(2) {
+                let from = (x + pair[0].0 * width, y + pair[0].1 * height);
+                let to = (x + pair[1].0 * width, y + pair[1].1 * height);
+                self.line(from, to, stroke, color);
+            }
+        }
+    }
+}
+
+/// Returns the polylines of a digit from 1 to 9 in the built-in stroke font.
+pub fn digit_strokes(digit: usize) -> &'static [&'static [(f64, f64)]] {
+    DIGIT_STROKES[digit - 1]
+}
+
+/// Returns the distance from a point to the line segment between `a` and `b`.
+fn distance_to_segment(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if 0.0 == length_squared {
+        0.0
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    let (nearest_x, nearest_y) = (a.0 + t * dx, a.1 + t * dy);
+    ((point.0 - nearest_x).powi(2) + (point.1 - nearest_y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_inside_bounds() {
+        let mut canvas = Canvas::new(40, 20, [255, 255, 255]);
+        canvas.fill_rect(0.0, 0.0, 10.0, 20.0, [0, 0, 0]);
+        canvas.line((30.0, -5.0), (30.0, 50.0), 2.0, [0, 0, 0]);
+        assert_eq!([0, 0, 0], canvas.pixel(9, 19));
+        assert_eq!([255, 255, 255], canvas.pixel(10, 0));
+        assert_eq!([0, 0, 0], canvas.pixel(29, 10));
+        assert_eq!(40 * 20 * 3, canvas.to_rgb_bytes().len());
+        let inked = |canvas: &Canvas| canvas.pixels.iter().filter(|pixel| pixel[0] < 128).count();
+        for digit in 1..=9 {
+            let mut canvas = Canvas::new(20, 30, [255, 255, 255]);
+            canvas.digit(digit, (4.0, 4.0), (12.0, 22.0), 2.0, [0, 0, 0]);
+            assert!(inked(&canvas) > 20, "digit: {}", digit);
+        }
+    }
+}
@@ -0,0 +1,578 @@
+//! Reads a puzzle from a PNG or JPEG image of a grid, such as a screenshot, a scan, or a photo.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  1. Ink      Pixels darker than their surroundings are ink. Images with a dark background are inverted.
+//!  2. Grid     The largest connected shape of ink is the grid, and its four outermost points are its corners.
+//!  3. Cells    Each of the 81 cells is sampled through the corners, so mild rotation and perspective are fine.
+//!              Shapes touching the edge of a cell are leftover grid lines, and shapes shorter than a third
+//!              of a cell are specks or pencil marks, so both are ignored.
+//!  4. Digits   The ink of each cell is scaled to a small square and compared with templates drawn from the
+//!              built-in stroke font. The confidence is how strongly the best digit beats the others.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  This is template matching, not general OCR. Printed sans-serif digits in grids with cells of about 30
+//!  pixels or more read reliably. Serif faces, handwriting, pencil marks drawn as large as the clues, small or
+//!  blurry grids, and photos with glare or strong perspective can be misread or missed, so check the result.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::raster::Canvas;
+use rayon::prelude::*;
+use std::io;
+use std::sync::OnceLock;
+
+/// The width and height of the patch sampled from each cell, in samples.
+const PATCH: usize = 48;
+
+/// The part of a cell on each side that is left out of its patch, to skip the grid lines.
+const INSET: f64 = 0.1;
+
+/// The width and height of a normalized digit.
+const GLYPH: usize = 16;
+
+/// The fraction of the height of a patch that a shape must span to be part of a digit.
+const MIN_HEIGHT: f64 = 0.3;
+
+/// The fraction of a patch that must be ink for its cell to hold a digit.
+const MIN_INK: f64 = 0.015;
+
+/// How much a difference in similarity counts towards the confidence; smaller is stricter.
+const TEMPERATURE: f64 = 0.04;
+
+/// A grayscale image, from 0 for black to 255 for white.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrayImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    /// Converts consecutive red, green, and blue bytes to grayscale.
+    pub fn from_rgb(width: usize, height: usize, rgb: &[u8]) -> Self {
+        let pixels = rgb
+            .chunks(3)
+            .map(|pixel| luma(pixel[0], pixel[1], pixel[2]))
+            .collect();
+        GrayImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Converts a canvas to grayscale.
+    pub fn from_canvas(canvas: &Canvas) -> Self {
+        GrayImage::from_rgb(canvas.width(), canvas.height(), &canvas.to_rgb_bytes())
+    }
+}
+
+/// The digit read from one cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    /// The digit from 1 to 9, or `None` if the cell is empty.
+    pub digit: Option<usize>,
+    /// How sure the classifier is of the digit, from 0 to 1. Empty cells are always 1.
+    pub confidence: f64,
+}
+
+/// Everything read from an image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recognition {
+    /// The cells in row-major order.
+    pub cells: Vec<Cell>,
+}
+
+impl Recognition {
+    /// Returns the puzzle as a string of 81 characters, with `.` for each empty square.
+    pub fn puzzle(&self) -> String {
+        self.cells
+            .iter()
+            .map(|cell| match cell.digit {
+                Some(digit) => (b'0' + digit as u8) as char,
+                None => '.',
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the cells holding a digit with less than the given confidence.
+    pub fn uncertain(&self, min_confidence: f64) -> Vec<usize> {
+        (0..81)
+            .filter(|&index| {
+                let cell = &self.cells[index];
+                cell.digit.is_some() && cell.confidence < min_confidence
+            })
+            .collect()
+    }
+}
+
+/// Decodes a PNG or JPEG image, telling them apart by their first bytes.
+pub fn decode(bytes: &[u8]) -> io::Result<GrayImage> {
+    if bytes.starts_with(b"\x89PNG") {
+        decode_png(bytes)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        decode_jpeg(bytes)
+    } else {
+        Err(invalid("the image must be a PNG or a JPEG"))
+    }
+}
+
+fn decode_png(bytes: &[u8]) -> io::Result<GrayImage> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(io::Error::other)?;
+    let data = &buffer[..info.buffer_size()];
+    let (width, height) = (info.width as usize, info.height as usize);
+    let over_white = |value: u8, alpha: u8| {
+        ((value as u32 * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8
+    };
+    let pixels = match info.color_type {
+        png::ColorType::Grayscale => data.to_vec(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks(2)
+            .map(|pixel| over_white(pixel[0], pixel[1]))
+            .collect(),
+        png::ColorType::Rgb => data
+            .chunks(3)
+            .map(|pixel| luma(pixel[0], pixel[1], pixel[2]))
+            .collect(),
+        png::ColorType::Rgba => data
+            .chunks(4)
+            .map(|pixel| over_white(luma(pixel[0], pixel[1], pixel[2]), pixel[3]))
+            .collect(),
+        png::ColorType::Indexed => return Err(invalid("the palette of the PNG was not expanded")),
+    };
+    Ok(GrayImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn decode_jpeg(bytes: &[u8]) -> io::Result<GrayImage> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    let data = decoder.decode().map_err(io::Error::other)?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| invalid("the JPEG has no image information"))?;
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => data,
+        // 16-bit samples are big-endian, so the first byte of each is the most significant.
+        jpeg_decoder::PixelFormat::L16 => data.chunks(2).map(|pixel| pixel[0]).collect(),
+        jpeg_decoder::PixelFormat::RGB24 => data
+            .chunks(3)
+            .map(|pixel| luma(pixel[0], pixel[1], pixel[2]))
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => data
+            .chunks(4)
+            .map(|pixel| {
+                let [c, m, y, k] = [pixel[0], pixel[1], pixel[2], pixel[3]].map(u32::from);
+                let channel = |ink: u32| ((255 - ink) * (255 - k) / 255) as u8;
+                luma(channel(c), channel(m), channel(y))
+            })
+            .collect(),
+    };
+    Ok(GrayImage {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    })
+}
+
+/// Finds the grid in an image and reads the digit in each of its cells.
+pub fn recognize(image: &GrayImage) -> io::Result<Recognition> {
+    let ink = ink(image);
+    let corners = find_grid(&ink, image.width, image.height)
+        .ok_or_else(|| invalid("could not find a grid in the image"))?;
+    let templates = templates();
+    let cells = (0..81)
+        .into_par_iter()
+        .map(|index| {
+            let patch = cell_patch(&ink, image.width, image.height, &corners, index);
+            match glyph(&patch) {
+                Some(glyph) => classify(&glyph, templates),
+                None => Cell {
+                    digit: None,
+                    confidence: 1.0,
+                },
+            }
+        })
+        .collect();
+    Ok(Recognition { cells })
+}
+
+/// Returns every pair of squares in the same row, column, or box that hold the same digit.
+pub fn conflicts(puzzle: &str) -> Vec<(usize, usize)> {
+    let digits: Vec<char> = puzzle.chars().collect();
+    let mut pairs = Vec::new();
+    for first in 0..81 {
+        for second in first + 1..81 {
+            let (a, b) = ((first / 9, first % 9), (second / 9, second % 9));
+            let is_peer = a.0 == b.0 || a.1 == b.1 || (a.0 / 3, a.1 / 3) == (b.0 / 3, b.1 / 3);
+            if is_peer && digits[first].is_ascii_digit() && digits[first] == digits[second] {
+                pairs.push((first, second));
+            }
+        }
+    }
+    pairs
+}
+
+/// Marks the pixels that are darker than their surroundings, inverting images with a dark background.
+fn ink(image: &GrayImage) -> Vec<bool> {
+    let (width, height) = (image.width, image.height);
+    let mean = image.pixels.iter().map(|&pixel| pixel as u64).sum::<u64>()
+        / image.pixels.len().max(1) as u64;
+    let value = |index: usize| {
+        let pixel = image.pixels[index] as i64;
+        if mean < 100 {
+            255 - pixel
+        } else {
+            pixel
+        }
+    };
+    // The sum of every pixel above and to the left of each position, for the mean of any window at once.
+    let mut sums = vec![0i64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row = 0;
+        for x in 0..width {
+            row += value(y * width + x);
+            sums[(y + 1) * (width + 1) + x + 1] = sums[y * (width + 1) + x + 1] + row;
+        }
+    }
+    let radius = (width.max(height) / 40).max(4);
+    (0..width * height)
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            let (x0, y0) = (x.saturating_sub(radius), y.saturating_sub(radius));
+            let (x1, y1) = ((x + radius + 1).min(width), (y + radius + 1).min(height));
+            let sum = sums[y1 * (width + 1) + x1]
+                - sums[y0 * (width + 1) + x1]
+                - sums[y1 * (width + 1) + x0]
+                + sums[y0 * (width + 1) + x0];
+            let area = ((x1 - x0) * (y1 - y0)) as i64;
+            value(index) * area < sum - 20 * area
+        })
+        .collect()
+}
+
+/// Returns the top left, top right, bottom right, and bottom left corners of the largest shape of ink.
+fn find_grid(ink: &[bool], width: usize, height: usize) -> Option<[(f64, f64); 4]> {
+    let mut seen = vec![false; ink.len()];
+    let mut best: Option<(usize, [(f64, f64); 4])> = None;
+    let mut stack = Vec::new();
+    for start in 0..ink.len() {
+        if !ink[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let (mut left, mut right, mut top, mut bottom) = (width, 0, height, 0);
+        // The pixels with the smallest and largest x + y and x - y are the corners of a grid, even when tilted.
+        let mut extremes = [
+            (i64::MAX, start),
+            (i64::MIN, start),
+            (i64::MIN, start),
+            (i64::MAX, start),
+        ];
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index % width, index / width);
+            left = left.min(x);
+            right = right.max(x);
+            top = top.min(y);
+            bottom = bottom.max(y);
+            let (sum, difference) = ((x + y) as i64, x as i64 - y as i64);
+            if sum < extremes[0].0 {
+                extremes[0] = (sum, index);
+            }
+            if difference > extremes[1].0 {
+                extremes[1] = (difference, index);
+            }
+            if sum > extremes[2].0 {
+                extremes[2] = (sum, index);
+            }
+            if difference < extremes[3].0 {
+                extremes[3] = (difference, index);
+            }
+            for dy in -1i64..=1 {
+                for dx in -1i64..=1 {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let neighbor = ny as usize * width + nx as usize;
+                    if ink[neighbor] && !seen[neighbor] {
+                        seen[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        let (box_width, box_height) = (right - left + 1, bottom - top + 1);
+        let area = box_width * box_height;
+        let is_square = box_width < 2 * box_height && box_height < 2 * box_width;
+        if is_square && box_width >= 45 && best.is_none_or(|(best, _)| area > best) {
+            let corner = |(_, index): (i64, usize)| {
+                ((index % width) as f64 + 0.5, (index / width) as f64 + 0.5)
+            };
+            best = Some((area, extremes.map(corner)));
+        }
+    }
+    best.map(|(_, corners)| corners)
+}
+
+/// Maps a point of the unit square onto the grid, where `(0, 0)` is its top left and `(1, 1)` its bottom right.
+fn map(corners: &[(f64, f64); 4], u: f64, v: f64) -> (f64, f64) {
+    let [top_left, top_right, bottom_right, bottom_left] = *corners;
+    let blend = |a: f64, b: f64, c: f64, d: f64| {
+        (1.0 - u) * (1.0 - v) * a + u * (1.0 - v) * b + u * v * c + (1.0 - u) * v * d
+    };
+    (
+        blend(top_left.0, top_right.0, bottom_right.0, bottom_left.0),
+        blend(top_left.1, top_right.1, bottom_right.1, bottom_left.1),
+    )
+}
+
+/// Samples the inside of a cell as a square patch of ink.
+fn cell_patch(
+    ink: &[bool],
+    width: usize,
+    height: usize,
+    corners: &[(f64, f64); 4],
+    index: usize,
+) -> Vec<bool> {
+    let (row, col) = ((index / 9) as f64, (index % 9) as f64);
+    let span = 1.0 - 2.0 * INSET;
+    let mut patch = Vec::with_capacity(PATCH * PATCH);
+    for y in 0..PATCH {
+        for x in 0..PATCH {
+            let u = (col + INSET + span * (x as f64 + 0.5) / PATCH as f64) / 9.0;
+            let v = (row + INSET + span * (y as f64 + 0.5) / PATCH as f64) / 9.0;
+            let (px, py) = map(corners, u, v);
+            let inside = px >= 0.0 && py >= 0.0 && (px as usize) < width && (py as usize) < height;
+            patch.push(inside && ink[py as usize * width + px as usize]);
+        }
+    }
+    patch
+}
+
+/// Keeps the shapes of a patch that could be part of a digit and scales them to fill a normalized square,
+/// or returns `None` if too little ink is left for a digit.
+fn glyph(patch: &[bool]) -> Option<Vec<f64>> {
+    let mut kept = vec![false; patch.len()];
+    let mut seen = vec![false; patch.len()];
+    for start in 0..patch.len() {
+        if !patch[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let (mut stack, mut shape) = (vec![start], Vec::new());
+        let (mut touches_edge, mut top, mut bottom) = (false, PATCH, 0);
+        while let Some(index) = stack.pop() {
+            shape.push(index);
+            let (x, y) = (index % PATCH, index / PATCH);
+            top = top.min(y);
+            bottom = bottom.max(y);
+            touches_edge |= 0 == x || 0 == y || PATCH - 1 == x || PATCH - 1 == y;
+            for (nx, ny) in neighbors(x, y) {
+                let neighbor = ny * PATCH + nx;
+                if patch[neighbor] && !seen[neighbor] {
+                    seen[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        // Short shapes are specks or pencil marks, and shapes touching the edge are what is left of the grid lines.
+        if !touches_edge && (bottom - top + 1) as f64 >= MIN_HEIGHT * PATCH as f64 {
+            for index in shape {
+                kept[index] = true;
+            }
+        }
+    }
+    let inked: Vec<(usize, usize)> = (0..kept.len())
+        .filter(|&index| kept[index])
+        .map(|index| (index % PATCH, index / PATCH))
+        .collect();
+    if (inked.len() as f64) < MIN_INK * (PATCH * PATCH) as f64 {
+        return None;
+    }
+    let left = inked.iter().map(|&(x, _)| x).min()? as f64;
+    let right = inked.iter().map(|&(x, _)| x).max()? as f64 + 1.0;
+    let top = inked.iter().map(|&(_, y)| y).min()? as f64;
+    let bottom = inked.iter().map(|&(_, y)| y).max()? as f64 + 1.0;
+    // Scale the longer side to fill the glyph, keeping the aspect ratio, which tells a 1 from a 7.
+    let scale = (right - left).max(bottom - top) / GLYPH as f64;
+    let (offset_x, offset_y) = (
+        left - (GLYPH as f64 * scale - (right - left)) / 2.0,
+        top - (GLYPH as f64 * scale - (bottom - top)) / 2.0,
+    );
+    const SAMPLES: usize = 3;
+    let mut glyph = vec![0.0; GLYPH * GLYPH];
+    for (index, value) in glyph.iter_mut().enumerate() {
+        let (gx, gy) = ((index % GLYPH) as f64, (index / GLYPH) as f64);
+        let mut covered = 0;
+        for sample in 0..SAMPLES * SAMPLES {
+            let sx = offset_x + (gx + (0.5 + (sample % SAMPLES) as f64) / SAMPLES as f64) * scale;
+            let sy = offset_y + (gy + (0.5 + (sample / SAMPLES) as f64) / SAMPLES as f64) * scale;
+            if sx >= 0.0 && sy >= 0.0 && (sx as usize) < PATCH && (sy as usize) < PATCH {
+                covered += kept[sy as usize * PATCH + sx as usize] as usize;
+            }
+        }
+        *value = covered as f64 / (SAMPLES * SAMPLES) as f64;
+    }
+    Some(normalize(&blur(&glyph)))
+}
+
+/// Returns the coordinates of the up to eight pixels around a pixel of a patch.
+fn neighbors(x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+    (-1i64..=1)
+        .flat_map(move |dy| (-1i64..=1).map(move |dx| (x as i64 + dx, y as i64 + dy)))
+        .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < PATCH as i64 && ny < PATCH as i64)
+        .map(|(nx, ny)| (nx as usize, ny as usize))
+}
+
+/// Spreads each value of a glyph over its neighbors, so strokes that are slightly apart still overlap.
+fn blur(glyph: &[f64]) -> Vec<f64> {
+    (0..GLYPH * GLYPH)
+        .map(|index| {
+            let (x, y) = ((index % GLYPH) as i64, (index / GLYPH) as i64);
+            let mut sum = 0.0;
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (0, 0)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx >= 0 && ny >= 0 && nx < GLYPH as i64 && ny < GLYPH as i64 {
+                    sum += glyph[ny as usize * GLYPH + nx as usize];
+                }
+            }
+            sum / 5.0
+        })
+        .collect()
+}
+
+/// Shifts a glyph to a mean of zero and scales it to a length of one, so a dot product is a correlation.
+fn normalize(glyph: &[f64]) -> Vec<f64> {
+    let mean = glyph.iter().sum::<f64>() / glyph.len() as f64;
+    let centered: Vec<f64> = glyph.iter().map(|value| value - mean).collect();
+    let length = centered
+        .iter()
+        .map(|value| value * value)
+        .sum::<f64>()
+        .sqrt();
+    centered
+        .iter()
+        .map(|value| value / length.max(f64::EPSILON))
+        .collect()
+}
+
+/// Returns the templates of every digit, drawn from the stroke font in several weights and widths.
+fn templates() -> &'static [(usize, Vec<f64>)] {
+    static TEMPLATES: OnceLock<Vec<(usize, Vec<f64>)>> = OnceLock::new();
+    TEMPLATES.get_or_init(|| {
+        let mut templates = Vec::new();
+        for digit in 1..=9 {
+            for &stroke in &[0.07, 0.11, 0.15] {
+                for &width in &[0.5, 0.6, 0.7] {
+                    let mut canvas = Canvas::new(PATCH, PATCH, [255, 255, 255]);
+                    let height = PATCH as f64 * 0.6;
+                    let corner = ((PATCH as f64 - width * height) / 2.0, PATCH as f64 * 0.2);
+                    canvas.digit(
+                        digit,
+                        corner,
+                        (width * height, height),
+                        stroke * height,
+                        [0, 0, 0],
+                    );
+                    let patch: Vec<bool> = GrayImage::from_canvas(&canvas)
+                        .pixels
+                        .iter()
+                        .map(|&pixel| pixel < 128)
+                        .collect();
+                    if let Some(glyph) = glyph(&patch) {
+                        templates.push((digit, glyph));
+                    }
+                }
+            }
+        }
+        templates
+    })
+}
+
+/// Picks the digit whose templates are most like the glyph.
+fn classify(glyph: &[f64], templates: &[(usize, Vec<f64>)]) -> Cell {
+    let mut scores = [f64::MIN; 9];
+    for (digit, template) in templates {
+        let score: f64 = glyph.iter().zip(template).map(|(a, b)| a * b).sum();
+        scores[digit - 1] = scores[digit - 1].max(score);
+    }
+    let best = (0..9)
+        .max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+        .expect("there are nine digits");
+    // The share of the best digit in a softmax over the scores of every digit.
+    let total: f64 = scores
+        .iter()
+        .map(|score| ((score - scores[best]) / TEMPERATURE).exp())
+        .sum();
+    Cell {
+        digit: Some(best + 1),
+        confidence: 1.0 / total,
+    }
+}
+
+fn luma(red: u8, green: u8, blue: u8) -> u8 {
+    ((299 * red as u32 + 587 * green as u32 + 114 * blue as u32) / 1000) as u8
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::formats::Grid;
+    use crate::sudoku::render::{self, Style};
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn reads_rendered_puzzles() {
+        for &size in &[300, 450, 700] {
+            let style = Style {
+                size,
+                ..Style::default()
+            };
+            let png = render::png(&Grid::new(PUZZLE), &style).unwrap();
+            let recognition = recognize(&decode(&png).unwrap()).unwrap();
+            assert_eq!(PUZZLE, recognition.puzzle(), "size: {}", size);
+            assert!(recognition.uncertain(0.5).is_empty(), "size: {}", size);
+        }
+        let blank = GrayImage::from_rgb(100, 100, &[255; 100 * 100 * 3]);
+        assert!(recognize(&blank).is_err());
+        assert!(decode(b"GIF89a").is_err());
+    }
+
+    #[test]
+    fn reads_screenshots_in_other_fonts() {
+        // Screenshots of the same puzzle drawn outside this crate, with DejaVu Sans and DejaVu Sans Mono
+        // digits, anti-aliased, in dark blue on gray thin lines and black box lines, with cells of 50 pixels.
+        let puzzle =
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        let fixtures: [&[u8]; 2] = [
+            include_bytes!("../../images/fixtures/dejavu-sans.png"),
+            include_bytes!("../../images/fixtures/dejavu-sans-mono.png"),
+        ];
+        for (index, png) in fixtures.iter().enumerate() {
+            let recognition = recognize(&decode(png).unwrap()).unwrap();
+            assert_eq!(puzzle, recognition.puzzle(), "fixture: {}", index);
+            assert!(conflicts(&recognition.puzzle()).is_empty());
+        }
+    }
+
+    #[test]
+    fn finds_conflicts() {
+        assert!(conflicts(PUZZLE).is_empty());
+        let mut puzzle = String::from(PUZZLE);
+        puzzle.replace_range(0..1, "2");
+        assert_eq!(vec![(0, 9)], conflicts(&puzzle));
+    }
+}
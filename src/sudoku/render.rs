@@ -0,0 +1,315 @@
+//! Draws print-quality images of puzzles as SVG or PNG.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Givens are bold and black. Digits filled in after the givens, such as a solution, are blue.
+//!  Candidates are small and gray, placed like the keys of a phone: 1 at the top left, 9 at the bottom right.
+//!  Boxes have thick borders and squares have thin borders.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  SVG uses text in the chosen font family. PNG draws digits with the built-in stroke font.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+
+use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::board::{SudokuBoard, SudokuSquare};
+use crate::sudoku::formats::Grid;
+use crate::sudoku::raster::{Canvas, Color};
+use std::io;
+
+/// The names of every image format, as accepted by [render](fn.render.html).
+pub const RENDER_FORMATS: [&str; 2] = ["svg", "png"];
+
+/// The smallest and largest image sizes in pixels, as accepted by [parse_size](fn.parse_size.html).
+pub const SIZES: (u32, u32) = (45, 4000);
+
+const WHITE: Color = [255, 255, 255];
+const BLACK: Color = [0, 0, 0];
+const BLUE: Color = [31, 95, 191];
+const GRAY: Color = [119, 119, 119];
+const LIGHT_GRAY: Color = [153, 153, 153];
+
+/// How a puzzle is drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    /// The width and height of the image in pixels.
+    pub size: u32,
+    /// The font family of the digits in SVG images.
+    pub font: String,
+    /// Whether to draw the candidates of every empty square.
+    pub candidates: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            size: 450,
+            font: String::from("Helvetica, Arial, sans-serif"),
+            candidates: false,
+        }
+    }
+}
+
+/// The positions and line widths of a drawing, in pixels.
+#[derive(Clone, Copy, Debug)]
+struct Layout {
+    margin: f64,
+    cell: f64,
+    thin: f64,
+    thick: f64,
+}
+
+impl Layout {
+    fn new(size: u32) -> Self {
+        let size = size as f64;
+        let margin = (size / 30.0).max(2.0);
+        let thin = (size / 450.0).max(1.0);
+        Layout {
+            margin,
+            cell: (size - 2.0 * margin) / 9.0,
+            thin,
+            thick: 3.0 * thin,
+        }
+    }
+
+    /// Returns the position of the line before the given row or column.
+    fn line(&self, index: usize) -> f64 {
+        self.margin + index as f64 * self.cell
+    }
+
+    /// Returns the width of the line before the given row or column.
+    fn line_width(&self, index: usize) -> f64 {
+        match index % 3 {
+            0 => self.thick,
+            _ => self.thin,
+        }
+    }
+}
+
+/// A digit to draw, centered on `(x, y)` with a height of `size` pixels.
+struct Glyph {
+    digit: usize,
+    x: f64,
+    y: f64,
+    size: f64,
+    is_bold: bool,
+    color: Color,
+}
+
+/// Draws a puzzle in the given image format.
+pub fn render(format: &str, grid: &Grid, style: &Style) -> io::Result<Vec<u8>> {
+    match format {
+        "svg" => Ok(svg(grid, style).into_bytes()),
+        "png" => png(grid, style),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown image format: {}", format),
+        )),
+    }
+}
+
+/// Parses an image size in pixels, which must be between the bounds of [SIZES](constant.SIZES.html).
+pub fn parse_size(s: &str) -> Result<u32, String> {
+    let (min, max) = SIZES;
+    match s.parse::<u32>() {
+        Ok(size) if (min..=max).contains(&size) => Ok(size),
+        _ => Err(format!(
+            "size must be a number of pixels from {} to {}: {}",
+            min, max, s
+        )),
+    }
+}
+
+/// Draws a puzzle as an SVG document.
+pub fn svg(grid: &Grid, style: &Style) -> String {
+    let layout = Layout::new(style.size);
+    let size = style.size;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+        size
+    );
+    svg += &format!(
+        "  <rect width=\"{0}\" height=\"{0}\" fill=\"white\"/>\n",
+        size
+    );
+    let (start, end) = (layout.line(0), layout.line(9));
+    for index in (0..=9).filter(|index| 0 != index % 3) {
+        let at = layout.line(index);
+        svg += &format!(
+            "  <path d=\"M{0:.2} {1:.2}V{2:.2}M{1:.2} {0:.2}H{2:.2}\" stroke=\"{3}\" stroke-width=\"{4:.2}\"/>\n",
+            at, start, end, hex(LIGHT_GRAY), layout.thin
+        );
+    }
+    for index in (0..=9).filter(|index| 0 == index % 3) {
+        let at = layout.line(index);
+        svg += &format!(
+            "  <path d=\"M{0:.2} {1:.2}V{2:.2}M{1:.2} {0:.2}H{2:.2}\" stroke=\"black\" stroke-width=\"{3:.2}\" stroke-linecap=\"square\"/>\n",
+            at, start, end, layout.thick
+        );
+    }
+    svg += &format!(
+        "  <g font-family=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+        escape(&style.font)
+    );
+    for glyph in glyphs(grid, style, &layout) {
+        svg += &format!(
+            "    <text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{:.2}\"{} fill=\"{}\">{}</text>\n",
+            glyph.x,
+            glyph.y,
+            glyph.size,
+            if glyph.is_bold {
+                " font-weight=\"bold\""
+            } else {
+                ""
+            },
+            hex(glyph.color),
+            glyph.digit
+        );
+    }
+    svg += "  </g>\n</svg>\n";
+    svg
+}
+
+/// Draws a puzzle as a PNG image.
+pub fn png(grid: &Grid, style: &Style) -> io::Result<Vec<u8>> {
+    let canvas = raster(grid, style);
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, canvas.width() as u32, canvas.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(&canvas.to_rgb_bytes())
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(bytes)
+}
+
+/// Draws a puzzle onto a canvas.
+pub fn raster(grid: &Grid, style: &Style) -> Canvas {
+    let layout = Layout::new(style.size);
+    let size = style.size as usize;
+    let mut canvas = Canvas::new(size, size, WHITE);
+    let (start, end) = (layout.line(0), layout.line(9));
+    let mut indices: Vec<usize> = (0..=9).collect();
+    // Thin lines are drawn first so that box borders cover them where they cross.
+    indices.sort_by_key(|index| 0 == index % 3);
+    for index in indices {
+        let (at, width) = (layout.line(index), layout.line_width(index));
+        let color = if 0 == index % 3 { BLACK } else { LIGHT_GRAY };
+        let (from, to) = (start - layout.thick / 2.0, end + layout.thick / 2.0);
+        canvas.fill_rect(at - width / 2.0, from, at + width / 2.0, to, color);
+        canvas.fill_rect(from, at - width / 2.0, to, at + width / 2.0, color);
+    }
+    for glyph in glyphs(grid, style, &layout) {
+        let (width, height) = (glyph.size * 0.55, glyph.size * 0.72);
+        let stroke = glyph.size * if glyph.is_bold { 0.12 } else { 0.08 };
+        let corner = (glyph.x - width / 2.0, glyph.y - height / 2.0);
+        canvas.digit(glyph.digit, corner, (width, height), stroke, glyph.color);
+    }
+    canvas
+}
+
+/// Returns every digit to draw: the values on the board and, if requested, the candidates of empty squares.
+fn glyphs(grid: &Grid, style: &Style, layout: &Layout) -> Vec<Glyph> {
+    let board = SudokuBoard::from(grid.values.as_str());
+    let mut glyphs = Vec::new();
+    for (index, (given, value)) in grid.givens.chars().zip(grid.values.chars()).enumerate() {
+        let (row, col) = (index / 9, index % 9);
+        let (x, y) = (layout.line(col), layout.line(row));
+        match value.to_digit(10) {
+            Some(digit) => glyphs.push(Glyph {
+                digit: digit as usize,
+                x: x + layout.cell / 2.0,
+                y: y + layout.cell / 2.0,
+                size: layout.cell * 0.7,
+                is_bold: '.' != given,
+                color: if '.' == given { BLUE } else { BLACK },
+            }),
+            None if style.candidates => {
+                let square = SudokuSquare::at(row, col);
+                let present = board.options(&square);
+                for digit in (1..=9).filter(|&digit| 0 == present & as_bit(digit)) {
+                    let (sub_row, sub_col) = ((digit - 1) / 3, (digit - 1) % 3);
+                    glyphs.push(Glyph {
+                        digit,
+                        x: x + layout.cell * (sub_col as f64 + 0.5) / 3.0,
+                        y: y + layout.cell * (sub_row as f64 + 0.5) / 3.0,
+                        size: layout.cell * 0.26,
+                        is_bold: false,
+                        color: GRAY,
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+    glyphs
+}
+
+fn hex([red, green, blue]: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", red, green, blue)
+}
+
+/// Escapes text for use inside an XML attribute.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIVENS: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+    const VALUES: &str =
+        "975.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn svg_styles() {
+        let grid = Grid::with_values(GIVENS, VALUES);
+        let style = Style {
+            font: String::from("\"Fira Sans\""),
+            ..Style::default()
+        };
+        let svg = svg(&grid, &style);
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(35, svg.matches("font-weight=\"bold\"").count());
+        assert_eq!(1, svg.matches(&hex(BLUE)).count());
+        assert!(svg.contains("&quot;Fira Sans&quot;"));
+        let with_candidates = super::svg(
+            &grid,
+            &Style {
+                candidates: true,
+                ..style
+            },
+        );
+        assert!(with_candidates.matches(&hex(GRAY)).count() > 45);
+    }
+
+    #[test]
+    fn png_image() {
+        let grid = Grid::new(GIVENS);
+        let style = Style {
+            size: 90,
+            ..Style::default()
+        };
+        let canvas = raster(&grid, &style);
+        assert_eq!((90, 90), (canvas.width(), canvas.height()));
+        assert_eq!(WHITE, canvas.pixel(0, 0));
+        assert_eq!(BLACK, canvas.pixel(45, 3));
+        let png = png(&grid, &style).unwrap();
+        assert_eq!(b"\x89PNG", &png[0..4]);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(Ok(45), parse_size("45"));
+        assert_eq!(Ok(4000), parse_size("4000"));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("44").is_err());
+        assert!(parse_size("4001").is_err());
+        assert!(parse_size("big").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Samples solutions of a puzzle at random, each about as likely as any other, without listing them all.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  While the board has more than 1000 solutions, the empty square with the fewest options is filled
+//!  with one of them, chosen with probability proportional to the number of solutions it leaves: exactly
+//!  when those are fewer than 1000, and otherwise as estimated by random probes of the search tree.
+//!  Once the board has fewer than 1000 solutions, they are listed and one of them is picked uniformly.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+//! The only approximation is the estimates, so sampling is exactly uniform for puzzles with fewer than
+//! 1000 solutions and closer to uniform the more probes each estimate takes.
+
+use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::board::{SudokuBoard, SudokuSquare};
+use crate::sudoku::estimate::estimate;
+use crate::sudoku::random::{mix, SplitMix64};
+
+/// The number of solutions below which a board's solutions are listed instead of estimated.
+const EXACT_BELOW: usize = 1000;
+
+/// The number of random probes behind each estimated count when none is chosen.
+pub const PROBES: usize = 1000;
+
+/// Returns `count` solutions of a puzzle string sampled at random, each as 81 digits, using `probes`
+/// random probes for every estimate. Returns no solutions if the puzzle has none.
+pub fn sample(puzzle: &str, count: usize, seed: u64, probes: usize) -> Vec<String> {
+    (0..count as u64)
+        .map_while(|index| sample_one(puzzle, mix(&[seed, index]), probes))
+        .collect()
+}
+
+/// Returns one solution of a puzzle string sampled at random, or `None` if it has no solutions.
+pub fn sample_one(puzzle: &str, seed: u64, probes: usize) -> Option<String> {
+    let mut random = SplitMix64::new(seed);
+    let mut puzzle = SudokuBoard::from(puzzle).to_string_puzzle();
+    loop {
+        let mut solutions = SudokuBoard::from(puzzle.as_str()).find_up_to(EXACT_BELOW);
+        if solutions.len() < EXACT_BELOW {
+            return match solutions.len() {
+                0 => None,
+                len => Some(solutions.swap_remove(random.below(len))),
+            };
+        }
+        let children = branches(&puzzle);
+        let weights: Vec<f64> = children
+            .iter()
+            .map(|child| weight(child, random.next_u64(), probes))
+            .collect();
+        puzzle = choose(children, &weights, &mut random)?;
+    }
+}
+
+/// Returns the [children](fn.children.html) of the puzzle, after filling squares with one option until
+/// there is a choice. Those squares keep every solution, so they are filled without weighing them.
+fn branches(puzzle: &str) -> Vec<String> {
+    let mut puzzle = String::from(puzzle);
+    loop {
+        let mut children = children(&puzzle);
+        if 1 != children.len() {
+            return children;
+        }
+        puzzle = children.swap_remove(0);
+    }
+}
+
+/// Returns the puzzle with the empty square with the fewest options, the first in row-major order winning
+/// ties, filled with each of its options in turn.
+fn children(puzzle: &str) -> Vec<String> {
+    let board = SudokuBoard::from(puzzle);
+    let square = (0..81)
+        .map(|index| SudokuSquare::at(index / 9, index % 9))
+        .filter(|square| 0 == board.value_at(square))
+        .min_by_key(|square| board.count_options(square));
+    let square = match square {
+        Some(square) => square,
+        None => return Vec::new(),
+    };
+    let options = board.options(&square);
+    let index = 9 * square.row() + square.col();
+    (1..=9)
+        .filter(|&value| 0 == options & as_bit(value))
+        .map(|value| {
+            let mut child = String::from(puzzle);
+            child.replace_range(index..=index, &value.to_string());
+            child
+        })
+        .collect()
+}
+
+/// Returns the number of solutions of a puzzle string: exact if it has fewer than 1000, and estimated
+/// from `probes` probes otherwise.
+fn weight(puzzle: &str, seed: u64, probes: usize) -> f64 {
+    let mut board = SudokuBoard::from(puzzle);
+    let found = board.find_up_to(EXACT_BELOW).len();
+    if found < EXACT_BELOW {
+        return found as f64;
+    }
+    // A branch with this many solutions must not be ruled out by probes that all got stuck.
+    estimate(&board, seed, probes, None, probes)
+        .mean
+        .max(EXACT_BELOW as f64)
+}
+
+/// Picks one of the choices with probability proportional to its weight, or `None` if every weight is 0.
+fn choose(mut choices: Vec<String>, weights: &[f64], random: &mut SplitMix64) -> Option<String> {
+    let total: f64 = weights.iter().sum();
+    if 0.0 == total {
+        return None;
+    }
+    let mut target = total * random.next_f64();
+    for (index, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return Some(choices.swap_remove(index));
+        }
+        target -= weight;
+    }
+    let last = weights.iter().rposition(|&weight| 0.0 < weight)?;
+    Some(choices.swap_remove(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::verify::verify;
+    use std::collections::HashMap;
+
+    #[test]
+    fn picks_every_solution_evenly() {
+        let puzzle =
+            ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+        let mut counts = HashMap::new();
+        for solution in sample(puzzle, 3500, 11, 100) {
+            assert!(verify(puzzle, &solution).is_empty());
+            *counts.entry(solution).or_insert(0) += 1;
+        }
+        assert_eq!(35, counts.len());
+        assert!(counts.values().all(|&count| 50 < count && count < 150));
+        assert!(sample("11", 3, 11, 100).is_empty());
+    }
+
+    #[test]
+    fn picks_branches_in_proportion_to_their_solutions() {
+        let puzzle =
+            ".75.....42139.5.7...8.....9..241....4.............24..3...9.7...5.3..6988........";
+        let branches = branches(puzzle);
+        let solutions: Vec<usize> = branches
+            .iter()
+            .map(|branch| SudokuBoard::from(branch.as_str()).count_solutions())
+            .collect();
+        // The first branch has too many solutions to list, so its weight is estimated.
+        assert_eq!(vec![3095, 599], solutions);
+
+        // Weigh and pick a branch the same way as sample_one, which would then descend into it.
+        let (draws, mut random) = (200, SplitMix64::new(13));
+        let mut picked = vec![0; branches.len()];
+        for _ in 0..draws {
+            let weights: Vec<f64> = branches
+                .iter()
+                .map(|branch| weight(branch, random.next_u64(), PROBES))
+                .collect();
+            let branch = choose(branches.clone(), &weights, &mut random).unwrap();
+            picked[branches.iter().position(|b| *b == branch).unwrap()] += 1;
+        }
+        let total: usize = solutions.iter().sum();
+        for (&count, &picked) in solutions.iter().zip(&picked) {
+            let p = count as f64 / total as f64;
+            let expected = draws as f64 * p;
+            let spread = 4.0 * (expected * (1.0 - p)).sqrt();
+            assert!(
+                (picked as f64 - expected).abs() < spread,
+                "picked {} of {} times, expected {:.1}",
+                picked,
+                draws,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn samples_sparse_grids() {
+        let puzzle = &SudokuBoard::from("123456789").to_string_puzzle();
+        let first = sample(puzzle, 2, 5, 100);
+        assert_eq!(2, first.len());
+        assert_ne!(first[0], first[1]);
+        for solution in &first {
+            assert!(verify(puzzle, solution).is_empty());
+        }
+        assert_eq!(first, sample(puzzle, 2, 5, 100));
+    }
+}
@@ -0,0 +1,268 @@
+//! Splits the search for every solution of a puzzle into disjoint slices that separate processes can search.
+//! ```text
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  The top of the search tree is expanded breadth-first: each level fills the empty square with the
+//!  fewest options, the first one in row-major order winning ties, once with every option. A level is
+//!  added until there are at least 32 branches per shard or every branch is solved.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//!  Branch k belongs to shard k % N, so every shard gets branches from across the whole tree.
+//!  Every process expands the same tree for the same puzzle and number of shards, whatever its heuristic,
+//!  so the shards never overlap and together cover every solution exactly once.
+//! ───────────────────────────────────────────────────────────────────────────────────────────────────────
+//! ```
+//! Branches are puzzle strings of 81 characters with `.` for each empty square, which any engine can search.
+
+use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::board::{SudokuBoard, SudokuSquare};
+use std::fmt;
+use std::str::FromStr;
+
+/// The names of every way the merge command combines the outputs of shards.
+pub const MERGE_MODES: [&str; 2] = ["counts", "solutions"];
+
+/// The number of branches the tree is expanded to for each shard.
+const BRANCHES_PER_SHARD: usize = 32;
+
+/// The largest number of shards, which keeps the expanded top of the tree small enough to hold in memory.
+pub const MAX_SHARDS: usize = 1024;
+
+/// The slice `index` of `count` slices of the search, numbered from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    /// Returns the branches of a puzzle's search that belong to this shard, in order.
+    pub fn branches(&self, puzzle: &str) -> Vec<String> {
+        branches(puzzle, BRANCHES_PER_SHARD * self.count)
+            .into_iter()
+            .skip(self.index)
+            .step_by(self.count)
+            .collect()
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    /// Parses a shard written as `i/N`, such as `0/4` for the first of four shards.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || format!("expected a shard such as 0/4, found {}", input);
+        let (index, count) = input.split_once('/').ok_or_else(error)?;
+        let index: usize = index.trim().parse().map_err(|_| error())?;
+        let count: usize = count.trim().parse().map_err(|_| error())?;
+        if MAX_SHARDS < count {
+            return Err(format!(
+                "the number of shards must be at most {}: {}",
+                MAX_SHARDS, input
+            ));
+        }
+        if count <= index {
+            return Err(format!(
+                "the shard index must be less than the number of shards: {}",
+                input
+            ));
+        }
+        Ok(Shard { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Expands the top of a puzzle's search tree until it has at least `min_branches` branches or every branch
+/// is solved, and returns the branches in order. Branches that leave a square without options are dropped,
+/// so an unsolvable puzzle has no branches.
+pub fn branches(puzzle: &str, min_branches: usize) -> Vec<String> {
+    let puzzle = SudokuBoard::from(puzzle);
+    if !puzzle.is_solvable() {
+        return Vec::new();
+    }
+    let mut branches = vec![puzzle.to_string_puzzle()];
+    while branches.len() < min_branches {
+        let mut next = Vec::with_capacity(9 * branches.len());
+        let mut is_expanded = false;
+        for branch in branches {
+            let board = SudokuBoard::from(branch.as_str());
+            let square = (0..81)
+                .map(|index| SudokuSquare::at(index / 9, index % 9))
+                .filter(|square| 0 == board.value_at(square))
+                .min_by_key(|square| board.count_options(square));
+            let square = match square {
+                Some(square) => square,
+                None => {
+                    next.push(branch);
+                    continue;
+                }
+            };
+            is_expanded = true;
+            let options = board.options(&square);
+            let index = 9 * square.row() + square.col();
+            for value in (1..=9).filter(|&value| 0 == options & as_bit(value)) {
+                let mut child = branch.clone();
+                child.replace_range(index..=index, &value.to_string());
+                next.push(child);
+            }
+        }
+        branches = next;
+        if !is_expanded {
+            break;
+        }
+    }
+    branches
+}
+
+/// Returns the number of solutions in the output of a count-all run, from its `Found:` line
+/// or from a file that holds nothing but the number.
+pub fn parse_count(output: &str) -> Option<usize> {
+    if let Ok(count) = output.trim().parse() {
+        return Some(count);
+    }
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Found:"))
+        .filter_map(|found| found.split_whitespace().next())
+        .find_map(|count| count.parse().ok())
+}
+
+/// The shard, puzzle, and number of solutions of a count-all run with --shard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShardCount {
+    pub shard: Shard,
+    pub puzzle: String,
+    pub count: usize,
+}
+
+/// Reads the output of a count-all run with --shard from its `Puzzle:`, `Shard:`, and `Found:` lines.
+pub fn parse_shard_count(output: &str) -> Option<ShardCount> {
+    let field = |name: &str| {
+        output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(name))
+            .find_map(|value| value.split_whitespace().next())
+    };
+    Some(ShardCount {
+        shard: field("Shard:")?.parse().ok()?,
+        puzzle: String::from(field("Puzzle:")?),
+        count: parse_count(output)?,
+    })
+}
+
+/// Returns the total count of the shards of a search, if they are every shard from 0 to N - 1 exactly once,
+/// all of the same puzzle and N.
+pub fn total(counts: &[ShardCount]) -> Result<usize, String> {
+    let first = counts.first().ok_or("there are no shards to merge")?;
+    let mut is_seen = vec![false; first.shard.count];
+    for count in counts {
+        if count.puzzle != first.puzzle {
+            return Err(format!(
+                "the shards are of different puzzles: {} and {}",
+                first.puzzle, count.puzzle
+            ));
+        }
+        if count.shard.count != first.shard.count {
+            return Err(format!(
+                "the shards split the search into different numbers of slices: {} and {}",
+                first.shard, count.shard
+            ));
+        }
+        if is_seen[count.shard.index] {
+            return Err(format!("shard {} is given more than once", count.shard));
+        }
+        is_seen[count.shard.index] = true;
+    }
+    match is_seen.iter().position(|&is_seen| !is_seen) {
+        Some(index) => Err(format!(
+            "shard {} is missing",
+            Shard {
+                index,
+                count: first.shard.count
+            }
+        )),
+        None => Ok(counts.iter().map(|count| count.count).sum()),
+    }
+}
+
+/// Returns the number of solutions in a file written by find-all, in either the compact or the board format.
+pub fn count_solutions_in(solutions: &str) -> usize {
+    let compact = solutions
+        .lines()
+        .filter(|line| 81 == line.len() && line.bytes().all(|byte| byte.is_ascii_digit()))
+        .count();
+    compact + solutions.matches('╔').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.....9..241....4...........8.24..3...9.7...5.3..6988.....31.";
+
+    #[test]
+    fn shards_cover_every_solution_once() {
+        let mut all = SudokuBoard::from(PUZZLE).find_up_to(100);
+        all.sort();
+        for count in 1..=5 {
+            let mut found = Vec::new();
+            for index in 0..count {
+                for branch in (Shard { index, count }).branches(PUZZLE) {
+                    found.extend(SudokuBoard::from(branch.as_str()).find_up_to(100));
+                }
+            }
+            found.sort();
+            assert_eq!(all, found, "{} shards", count);
+        }
+        assert!(branches("11", 8).is_empty());
+        assert_eq!(vec![all[0].clone()], branches(&all[0], 8));
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(Ok(Shard { index: 2, count: 8 }), "2/8".parse());
+        assert_eq!("2/8", Shard { index: 2, count: 8 }.to_string());
+        assert!("8/8".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+        assert!("0/1024".parse::<Shard>().is_ok());
+        assert!("0/1025".parse::<Shard>().is_err());
+        assert_eq!(
+            Some(35),
+            parse_count("\n  Found: 35 solutions\n  Time:  0.1 seconds\n")
+        );
+        assert_eq!(Some(1), parse_count("1\n"));
+        assert_eq!(None, parse_count("  Time:  0.1 seconds\n"));
+        let board = SudokuBoard::from(PUZZLE).find_up_to(1).remove(0);
+        let solutions = format!("{}\n{}", board, SudokuBoard::from(board.as_str()));
+        assert_eq!(2, count_solutions_in(&solutions));
+    }
+
+    #[test]
+    fn merging_counts() {
+        let output = |index: usize, puzzle: &str, count: usize| {
+            format!(
+                "\n  Puzzle: {}\n  Shard: {}/3 with 32 branches\n\n  Found: {} solutions\n",
+                puzzle, index, count
+            )
+        };
+        let counts: Vec<ShardCount> = (0..3)
+            .map(|index| parse_shard_count(&output(index, PUZZLE, 10 + index)).unwrap())
+            .collect();
+        assert_eq!(Shard { index: 1, count: 3 }, counts[1].shard);
+        assert_eq!(PUZZLE, counts[1].puzzle);
+        assert_eq!(Ok(33), total(&counts));
+        assert!(total(&counts[..2]).is_err());
+        assert!(total(&[counts[0].clone(), counts[0].clone(), counts[2].clone()]).is_err());
+        let other = parse_shard_count(&output(2, &PUZZLE.replace('7', "."), 10)).unwrap();
+        assert!(total(&[counts[0].clone(), counts[1].clone(), other]).is_err());
+        let mut halves = counts.clone();
+        halves[2].shard = Shard { index: 1, count: 2 };
+        assert!(total(&halves).is_err());
+        assert!(total(&[]).is_err());
+        assert_eq!(None, parse_shard_count("  Found: 35 solutions\n"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Recording and reading traces of the sequential search as JSON lines.
+//! ```text
+//! {"event":"start","puzzle":".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31."}
+//! {"event":"branch","row":0,"col":0,"options":1,"depth":0,"micros":9}
+//! {"event":"fill","row":0,"col":0,"value":6,"depth":1,"micros":12}
+//! {"event":"fill","row":0,"col":3,"value":9,"depth":2,"micros":15}
+//! {"event":"solution","depth":25,"micros":61}
+//! {"event":"clear","row":0,"col":3,"depth":2,"micros":64}
+//! ```
+
+use crate::sudoku::board::SearchEvent;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::Instant;
+
+/// Writes each search event to a file as a single line of JSON.
+pub struct Trace {
+    writer: BufWriter<File>,
+    start: Instant,
+    error: Option<io::Error>,
+}
+
+impl Trace {
+    /// Creates a trace file at the given path and records the puzzle being searched.
+    pub fn create(path: &str, puzzle: &str) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", json!({ "event": "start", "puzzle": puzzle }))?;
+        Ok(Trace {
+            writer,
+            start: Instant::now(),
+            error: None,
+        })
+    }
+
+    /// Records a search event along with the microseconds elapsed since the trace was created.
+    /// The first write error is kept and returned by [finish](#method.finish).
+    pub fn record(&mut self, event: SearchEvent) {
+        if self.error.is_some() {
+            return;
+        }
+        let micros = self.start.elapsed().as_micros() as u64;
+        let line = match event {
+            SearchEvent::Fill {
+                row,
+                col,
+                value,
+                depth,
+            } => json!({
+                "event": "fill", "row": row, "col": col, "value": value, "depth": depth, "micros": micros
+            }),
+            SearchEvent::Clear { row, col, depth } => json!({
+                "event": "clear", "row": row, "col": col, "depth": depth, "micros": micros
+            }),
+            SearchEvent::Solution { depth } => json!({
+                "event": "solution", "depth": depth, "micros": micros
+            }),
+            SearchEvent::Branch {
+                row,
+                col,
+                options,
+                depth,
+            } => json!({
+                "event": "branch", "row": row, "col": col, "options": options, "depth": depth, "micros": micros
+            }),
+            SearchEvent::DeadEnd { depth } => json!({
+                "event": "dead-end", "depth": depth, "micros": micros
+            }),
+        };
+        if let Err(e) = writeln!(self.writer, "{}", line) {
+            self.error = Some(e);
+        }
+    }
+
+    /// Flushes the trace to disk, returning the first error encountered while recording.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()
+    }
+}
+
+/// Reads a trace file, returning the puzzle that was searched and every recorded event in order.
+pub fn read_trace(path: &str) -> io::Result<(String, Vec<SearchEvent>)> {
+    read_trace_from(BufReader::new(File::open(path)?))
+}
+
+/// Reads a trace from any reader, such as stdin, returning the puzzle and every recorded event in order.
+pub fn read_trace_from<R: BufRead>(reader: R) -> io::Result<(String, Vec<SearchEvent>)> {
+    let mut lines = reader.lines();
+    let header = parse_line(&lines.next().unwrap_or_else(|| Ok(String::new()))?)?;
+    let puzzle = match (field_str(&header, "event"), field_str(&header, "puzzle")) {
+        (Some("start"), Some(puzzle)) => String::from(puzzle),
+        _ => return Err(invalid("trace must begin with a start event")),
+    };
+    let mut events = Vec::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(parse_event(&parse_line(&line)?)?);
+    }
+    Ok((puzzle, events))
+}
+
+/// Parses a single line of the trace as JSON.
+fn parse_line(line: &str) -> io::Result<Value> {
+    serde_json::from_str(line).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Converts a parsed line of the trace into a search event.
+fn parse_event(value: &Value) -> io::Result<SearchEvent> {
+    let square = |key| match field_usize(value, key) {
+        Some(index) if index < 9 => Ok(index),
+        _ => Err(invalid(&format!(
+            "event is missing a valid \"{}\": {}",
+            key, value
+        ))),
+    };
+    let depth = field_usize(value, "depth").unwrap_or(0);
+    match field_str(value, "event") {
+        Some("fill") => match field_usize(value, "value") {
+            Some(digit) if (1..=9).contains(&digit) => Ok(SearchEvent::Fill {
+                row: square("row")?,
+                col: square("col")?,
+                value: digit,
+                depth,
+            }),
+            _ => Err(invalid(&format!(
+                "fill is missing a valid \"value\": {}",
+                value
+            ))),
+        },
+        Some("clear") => Ok(SearchEvent::Clear {
+            row: square("row")?,
+            col: square("col")?,
+            depth,
+        }),
+        Some("solution") => Ok(SearchEvent::Solution { depth }),
+        Some("branch") => Ok(SearchEvent::Branch {
+            row: square("row")?,
+            col: square("col")?,
+            options: field_usize(value, "options").unwrap_or(0) as u32,
+            depth,
+        }),
+        Some("dead-end") => Ok(SearchEvent::DeadEnd { depth }),
+        _ => Err(invalid(&format!("unknown event: {}", value))),
+    }
+}
+
+fn field_str<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn field_usize(value: &Value, key: &str) -> Option<usize> {
+    value.get(key).and_then(Value::as_u64).map(|n| n as usize)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::SudokuBoard;
+    use std::env;
+
+    #[test]
+    fn round_trip() {
+        let puzzle =
+            ".75.....4.1...5.7...8.7...9..2417...4.......1...8.24..3...9.7...5.3.4..88.....31.";
+        let path = env::temp_dir().join("sudoku-cli-trace-round-trip.jsonl");
+        let path = path.to_str().unwrap();
+        let mut trace = Trace::create(path, puzzle).unwrap();
+        let mut recorded = Vec::new();
+        SudokuBoard::from(puzzle).search_observed(true, &mut |_, event| {
+            trace.record(event);
+            recorded.push(event);
+        });
+        trace.finish().unwrap();
+        let (read_puzzle, events) = read_trace(path).unwrap();
+        assert_eq!(puzzle, read_puzzle);
+        assert_eq!(recorded, events);
+    }
+
+    #[test]
+    fn rejects_missing_header() {
+        let path = env::temp_dir().join("sudoku-cli-trace-no-header.jsonl");
+        std::fs::write(&path, "{\"event\":\"solution\",\"depth\":3}\n").unwrap();
+        assert!(read_trace(path.to_str().unwrap()).is_err());
+    }
+}
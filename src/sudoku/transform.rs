@@ -0,0 +1,289 @@
+//! Transformations that turn a valid puzzle into a different-looking but equivalent one.
+//! Each transformation moves squares and relabels digits without changing the structure of the puzzle,
+//! so a transformed solution is always the solution of the transformed puzzle.
+//! Puzzles are strings of 81 characters as returned by
+//! [to_string_puzzle](../board/struct.SudokuBoard.html#method.to_string_puzzle), with `.` for each empty square.
+
+use crate::sudoku::random::SplitMix64;
+
+/// The names of every reflection, as accepted by [Reflection::from_name](enum.Reflection.html#method.from_name).
+pub const REFLECTIONS: [&str; 4] = ["horizontal", "vertical", "diagonal", "anti-diagonal"];
+
+/// A line across the grid to mirror a puzzle over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reflection {
+    /// Mirrors top to bottom, across the middle row.
+    Horizontal,
+    /// Mirrors left to right, across the middle column.
+    Vertical,
+    /// Mirrors across the line from the top left to the bottom right, the same as a transpose.
+    Diagonal,
+    /// Mirrors across the line from the top right to the bottom left.
+    AntiDiagonal,
+}
+
+impl Reflection {
+    /// Returns the reflection with the given name, as listed in [REFLECTIONS](constant.REFLECTIONS.html).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "horizontal" => Some(Reflection::Horizontal),
+            "vertical" => Some(Reflection::Vertical),
+            "diagonal" => Some(Reflection::Diagonal),
+            "anti-diagonal" => Some(Reflection::AntiDiagonal),
+            _ => None,
+        }
+    }
+}
+
+/// A combination of square moves and digit relabeling.
+/// The square at `index` of the transformed puzzle is the square at `squares[index]` of the original,
+/// with its digit replaced by `digits[digit]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transform {
+    squares: [usize; 81],
+    digits: [u8; 10],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Transform {
+    /// Returns the transformation that leaves every puzzle unchanged.
+    pub fn identity() -> Self {
+        let mut squares = [0; 81];
+        for (index, square) in squares.iter_mut().enumerate() {
+            *square = index;
+        }
+        Transform {
+            squares,
+            digits: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        }
+    }
+
+    /// Returns a transformation that moves the square at `(row, col)` to `to(row, col)`.
+    fn moving<F: Fn(usize, usize) -> (usize, usize)>(to: F) -> Self {
+        let mut transform = Transform::identity();
+        for row in 0..9 {
+            for col in 0..9 {
+                let (to_row, to_col) = to(row, col);
+                transform.squares[to_row * 9 + to_col] = row * 9 + col;
+            }
+        }
+        transform
+    }
+
+    /// Rotates a puzzle clockwise by the given number of quarter turns.
+    pub fn rotate(quarter_turns: usize) -> Self {
+        match quarter_turns % 4 {
+            0 => Transform::identity(),
+            1 => Transform::moving(|row, col| (col, 8 - row)),
+            2 => Transform::moving(|row, col| (8 - row, 8 - col)),
+            _ => Transform::moving(|row, col| (8 - col, row)),
+        }
+    }
+
+    /// Mirrors a puzzle across a line.
+    pub fn reflect(reflection: Reflection) -> Self {
+        match reflection {
+            Reflection::Horizontal => Transform::moving(|row, col| (8 - row, col)),
+            Reflection::Vertical => Transform::moving(|row, col| (row, 8 - col)),
+            Reflection::Diagonal => Transform::transpose(),
+            Reflection::AntiDiagonal => Transform::moving(|row, col| (8 - col, 8 - row)),
+        }
+    }
+
+    /// Swaps the rows and columns of a puzzle.
+    pub fn transpose() -> Self {
+        Transform::moving(|row, col| (col, row))
+    }
+
+    /// Replaces each digit `d` with `digits[d - 1]`. The digits must be a permutation of `1..=9`.
+    pub fn relabel(digits: [u8; 9]) -> Self {
+        let mut transform = Transform::identity();
+        transform.digits[1..].copy_from_slice(&digits);
+        transform
+    }
+
+    /// Reorders the rows so that row `i` of the transformed puzzle is row `rows[i]` of the original.
+    /// The order must keep the rows of each band together.
+    pub fn permute_rows(rows: [usize; 9]) -> Self {
+        Transform::moving(|row, col| {
+            let to_row = rows.iter().position(|&from| from == row).unwrap_or(row);
+            (to_row, col)
+        })
+    }
+
+    /// Reorders the bands so that band `i` of the transformed puzzle is band `bands[i]` of the original.
+    pub fn permute_bands(bands: [usize; 3]) -> Self {
+        let mut rows = [0; 9];
+        for (index, row) in rows.iter_mut().enumerate() {
+            *row = bands[index / 3] * 3 + index % 3;
+        }
+        Transform::permute_rows(rows)
+    }
+
+    /// Returns a random combination of a transpose, band and row swaps, stack and column swaps, and a relabeling.
+    /// Every combination is equally likely.
+    pub fn random(rng: &mut SplitMix64) -> Self {
+        let mut lines = || {
+            let mut bands = [0, 1, 2];
+            rng.shuffle(&mut bands);
+            let mut order = [0; 9];
+            for (band, &from) in bands.iter().enumerate() {
+                let mut within = [0, 1, 2];
+                rng.shuffle(&mut within);
+                for (i, &line) in within.iter().enumerate() {
+                    order[band * 3 + i] = from * 3 + line;
+                }
+            }
+            order
+        };
+        let rows = lines();
+        let cols = lines();
+        let mut transform = Transform::permute_rows(rows).then(&Transform::transpose());
+        transform = transform.then(&Transform::permute_rows(cols));
+        if 0 == rng.below(2) {
+            transform = transform.then(&Transform::transpose());
+        }
+        let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        rng.shuffle(&mut digits);
+        transform.then(&Transform::relabel(digits))
+    }
+
+    /// Returns the transformation that applies this one and then `next`.
+    pub fn then(&self, next: &Transform) -> Self {
+        let mut combined = Transform::identity();
+        for (index, square) in combined.squares.iter_mut().enumerate() {
+            *square = self.squares[next.squares[index]];
+        }
+        for (digit, label) in combined.digits.iter_mut().enumerate() {
+            *label = next.digits[self.digits[digit] as usize];
+        }
+        combined
+    }
+
+    /// Returns the index of the square of the original puzzle that moves to `index`.
+    pub fn source(&self, index: usize) -> usize {
+        self.squares[index]
+    }
+
+    /// Applies the transformation to a puzzle string.
+    pub fn apply(&self, puzzle: &str) -> String {
+        let values = puzzle.as_bytes();
+        self.squares
+            .iter()
+            .map(|&from| match (values[from] as char).to_digit(10) {
+                Some(digit) => (b'0' + self.digits[digit as usize]) as char,
+                None => '.',
+            })
+            .collect()
+    }
+}
+
+/// Parses a relabeling such as `912345678`, where the digit at position `d - 1` replaces the digit `d`.
+pub fn parse_digits(s: &str) -> Result<[u8; 9], String> {
+    let order = parse_order(s, 9, 1)?;
+    let mut digits = [0; 9];
+    for (digit, &value) in digits.iter_mut().zip(&order) {
+        *digit = value as u8;
+    }
+    Ok(digits)
+}
+
+/// Parses a row order such as `102345876`, which must keep the rows of each band together.
+pub fn parse_rows(s: &str) -> Result<[usize; 9], String> {
+    let order = parse_order(s, 9, 0)?;
+    if (0..9).any(|row| order[row] / 3 != order[row - row % 3] / 3) {
+        return Err(format!(
+            "row order must keep the rows of each band together: {}",
+            s
+        ));
+    }
+    let mut rows = [0; 9];
+    rows.copy_from_slice(&order);
+    Ok(rows)
+}
+
+/// Parses a band order such as `201`.
+pub fn parse_bands(s: &str) -> Result<[usize; 3], String> {
+    let order = parse_order(s, 3, 0)?;
+    let mut bands = [0; 3];
+    bands.copy_from_slice(&order);
+    Ok(bands)
+}
+
+/// Parses a permutation of `first..first + len` written as consecutive digits.
+fn parse_order(s: &str, len: usize, first: usize) -> Result<Vec<usize>, String> {
+    let order: Vec<usize> = s
+        .chars()
+        .filter_map(|c| c.to_digit(10).map(|digit| digit as usize))
+        .collect();
+    let mut sorted = order.clone();
+    sorted.sort_unstable();
+    if s.len() != len || sorted != (first..first + len).collect::<Vec<_>>() {
+        return Err(format!(
+            "expected each of {}..={} exactly once, found: {}",
+            first,
+            first + len - 1,
+            s
+        ));
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::{SudokuBoard, Uniqueness};
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    fn solution(puzzle: &str) -> String {
+        match SudokuBoard::from(puzzle).check_unique() {
+            Uniqueness::Unique(solution) => SudokuBoard::from(solution).to_string_puzzle(),
+            _ => panic!("puzzle should have a unique solution"),
+        }
+    }
+
+    #[test]
+    fn preserves_solutions() {
+        let solved = solution(PUZZLE);
+        let mut rng = SplitMix64::new(11);
+        let transforms = vec![
+            Transform::rotate(1),
+            Transform::rotate(2),
+            Transform::reflect(Reflection::Vertical),
+            Transform::reflect(Reflection::AntiDiagonal),
+            Transform::relabel(parse_digits("912345678").unwrap()),
+            Transform::permute_rows(parse_rows("102345876").unwrap()),
+            Transform::permute_bands(parse_bands("201").unwrap()),
+            Transform::random(&mut rng),
+        ];
+        for transform in transforms {
+            let puzzle = transform.apply(PUZZLE);
+            assert_ne!(PUZZLE, puzzle);
+            assert_eq!(transform.apply(&solved), solution(&puzzle));
+        }
+    }
+
+    #[test]
+    fn compositions() {
+        let quarter = Transform::rotate(1);
+        let half = quarter.then(&quarter);
+        assert_eq!(Transform::rotate(2), half);
+        assert_eq!(Transform::identity(), half.then(&half));
+        assert_eq!(
+            Transform::reflect(Reflection::Horizontal).then(&Transform::transpose()),
+            Transform::rotate(1)
+        );
+        assert!(parse_rows("345012678").is_ok());
+        assert!(parse_rows("102345687").is_ok());
+        assert!(parse_rows("312045678").is_err());
+        assert!(parse_bands("11").is_err());
+        assert!(parse_digits("012345678").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Builds the branching structure of the sequential search and writes it as a Graphviz DOT graph.
+//! ```text
+//!            ┌────────────┐
+//!            │    r4c1    │   Each node names the square chosen to branch on and its option count.
+//!            │ 2 options  │   Each edge is labeled with the value tried in that square.
+//!            └────────────┘   Solution leaves are green; subtrees without a solution are red.
+//!             3 /      \ 8    Nodes below the maximum depth are folded into their ancestor's label.
+//!     ┌──────────┐  ┌──────────┐
+//!     │ solution │  │ dead end │
+//!     └──────────┘  └──────────┘
+//! ```
+
+use crate::sudoku::board::SearchEvent;
+use std::io;
+use std::io::Write;
+
+/// A single node of the search tree.
+#[derive(Clone, Debug, Default)]
+struct Node {
+    /// The index of this node's parent along with the value that led here.
+    parent: Option<(usize, usize)>,
+    /// The `(row, col, options)` of the square chosen to branch on at this node.
+    branch: Option<(usize, usize, u32)>,
+    is_dead_end: bool,
+    is_solution: bool,
+    /// The number of solutions found in this node's subtree.
+    solutions: usize,
+    /// The number of descendant nodes hidden because they are deeper than the maximum depth.
+    hidden_nodes: usize,
+}
+
+/// Collects search events into a tree of nodes, up to a maximum depth.
+#[derive(Clone, Debug)]
+pub struct SearchTree {
+    nodes: Vec<Node>,
+    /// The index of the current node at each depth of the search.
+    path: Vec<usize>,
+    max_depth: usize,
+}
+
+impl SearchTree {
+    /// Creates a tree containing only the root, which keeps nodes up to `max_depth` squares deep.
+    pub fn new(max_depth: usize) -> Self {
+        SearchTree {
+            nodes: vec![Node::default()],
+            path: vec![0],
+            max_depth,
+        }
+    }
+
+    /// Updates the tree with the next event of the search.
+    pub fn record(&mut self, event: SearchEvent) {
+        match event {
+            SearchEvent::Branch {
+                row,
+                col,
+                options,
+                depth,
+            } => {
+                if let Some(node) = self.visible_node(depth) {
+                    node.branch = Some((row, col, options));
+                    node.is_dead_end = 0 == options;
+                }
+            }
+            SearchEvent::Fill { value, depth, .. } => {
+                self.path.truncate(depth);
+                if depth > self.max_depth {
+                    self.nodes[self.path[self.max_depth]].hidden_nodes += 1;
+                    return;
+                }
+                let parent = self.path[depth - 1];
+                self.nodes.push(Node {
+                    parent: Some((parent, value)),
+                    ..Node::default()
+                });
+                self.path.push(self.nodes.len() - 1);
+            }
+            SearchEvent::Clear { depth, .. } => self.path.truncate(depth),
+            SearchEvent::Solution { depth } => {
+                for &index in &self.path {
+                    self.nodes[index].solutions += 1;
+                }
+                if let Some(node) = self.visible_node(depth) {
+                    node.is_solution = true;
+                }
+            }
+            SearchEvent::DeadEnd { depth } => {
+                if let Some(node) = self.visible_node(depth) {
+                    node.is_dead_end = true;
+                }
+            }
+        }
+    }
+
+    /// Returns the node at the given depth of the current path, unless it is deeper than the maximum.
+    fn visible_node(&mut self, depth: usize) -> Option<&mut Node> {
+        if depth > self.max_depth {
+            return None;
+        }
+        let index = *self.path.get(depth)?;
+        self.nodes.get_mut(index)
+    }
+
+    /// Returns the number of nodes kept in the tree.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the tree contains only the root.
+    pub fn is_empty(&self) -> bool {
+        1 == self.nodes.len()
+    }
+
+    /// Writes the tree as a Graphviz DOT graph.
+    pub fn write_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "digraph search {{")?;
+        writeln!(
+            writer,
+            "    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];"
+        )?;
+        writeln!(writer, "    edge [fontname=\"Helvetica\"];")?;
+        for (index, node) in self.nodes.iter().enumerate() {
+            let mut label = if node.is_solution {
+                String::from("solution")
+            } else if let Some((row, col, options)) = node.branch {
+                format!(
+                    "r{}c{}\\n{} option{}",
+                    row,
+                    col,
+                    options,
+                    plural(options as usize)
+                )
+            } else if node.is_dead_end {
+                String::from("dead end")
+            } else {
+                String::new()
+            };
+            if 0 < node.hidden_nodes {
+                label += &format!(
+                    "\\n+{} deeper node{}\\n{} solution{}",
+                    node.hidden_nodes,
+                    plural(node.hidden_nodes),
+                    node.solutions,
+                    plural(node.solutions),
+                );
+            }
+            let color = if node.is_solution {
+                "palegreen"
+            } else if 0 == node.solutions {
+                "lightpink"
+            } else {
+                "white"
+            };
+            writeln!(
+                writer,
+                "    n{} [label=\"{}\", fillcolor={}];",
+                index, label, color
+            )?;
+            if let Some((parent, value)) = node.parent {
+                writeln!(
+                    writer,
+                    "    n{} -> n{} [label=\"{}\"];",
+                    parent, index, value
+                )?;
+            }
+        }
+        writeln!(writer, "}}")
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if 1 == count {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::SudokuBoard;
+
+    #[test]
+    fn depth_limit() {
+        let puzzle =
+            ".75.....4.1...5.7...8.7...9..2417...4.......1...8.24..3...9.7...5.3.4..88.....31.";
+        let mut full = SearchTree::new(81);
+        let mut shallow = SearchTree::new(2);
+        let count = SudokuBoard::from(puzzle).search_observed(true, &mut |_, event| {
+            full.record(event);
+            shallow.record(event);
+        });
+        assert_eq!(10, count);
+        assert_eq!(count, full.nodes[0].solutions);
+        assert_eq!(
+            count,
+            full.nodes.iter().filter(|node| node.is_solution).count()
+        );
+        assert_eq!(count, shallow.nodes[0].solutions);
+        let hidden: usize = shallow.nodes.iter().map(|node| node.hidden_nodes).sum();
+        assert_eq!(full.len(), shallow.len() + hidden);
+        let mut dot = Vec::new();
+        shallow.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("digraph search {"));
+        assert_eq!(shallow.len() - 1, dot.matches(" -> ").count());
+    }
+}
@@ -0,0 +1,252 @@
+//! Checks a proposed solution against its puzzle and reports every rule it breaks.
+//! Puzzles and solutions are strings of 81 characters as returned by
+//! [to_string_puzzle](../board/struct.SudokuBoard.html#method.to_string_puzzle), with `.` for each empty square.
+//! Repeated values are found with the row, column, and box masks of [SudokuData](../data/struct.SudokuData.html).
+
+use crate::sudoku::bitwise::as_bit;
+use crate::sudoku::data::SudokuData;
+use std::fmt;
+
+/// A row, column, or box of the board, numbered from 0 to 8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum House {
+    Row(usize),
+    Col(usize),
+    Box(usize),
+}
+
+impl House {
+    /// Returns true if the square at `(row, col)` belongs to the house.
+    fn contains(self, row: usize, col: usize) -> bool {
+        match self {
+            House::Row(index) => row == index,
+            House::Col(index) => col == index,
+            House::Box(index) => 3 * (row / 3) + col / 3 == index,
+        }
+    }
+}
+
+impl fmt::Display for House {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            House::Row(index) => write!(f, "row {}", index),
+            House::Col(index) => write!(f, "column {}", index),
+            House::Box(index) => write!(f, "box {}", index),
+        }
+    }
+}
+
+/// A rule that a proposed solution breaks, with the `(row, col)` of every square involved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// A given of the puzzle is missing or has a different value in the solution.
+    ChangedGiven {
+        square: (usize, usize),
+        given: usize,
+        value: usize,
+    },
+    /// A square of the solution is empty.
+    Empty { square: (usize, usize) },
+    /// A value appears more than once in a row, column, or box.
+    Repeated {
+        house: House,
+        value: usize,
+        squares: Vec<(usize, usize)>,
+    },
+}
+
+impl Violation {
+    /// Returns the `(row, col)` of every square involved in the violation.
+    pub fn squares(&self) -> Vec<(usize, usize)> {
+        match self {
+            Violation::ChangedGiven { square, .. } | Violation::Empty { square } => vec![*square],
+            Violation::Repeated { squares, .. } => squares.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::ChangedGiven {
+                square: (row, col),
+                given,
+                value: 0,
+            } => write!(f, "r{}c{} is empty but the given is {}", row, col, given),
+            Violation::ChangedGiven {
+                square: (row, col),
+                given,
+                value,
+            } => write!(
+                f,
+                "r{}c{} is {} but the given is {}",
+                row, col, value, given
+            ),
+            Violation::Empty { square: (row, col) } => write!(f, "r{}c{} is empty", row, col),
+            Violation::Repeated {
+                house,
+                value,
+                squares,
+            } => {
+                let squares: Vec<String> = squares
+                    .iter()
+                    .map(|(row, col)| format!("r{}c{}", row, col))
+                    .collect();
+                write!(f, "{} has {} at {}", house, value, squares.join(", "))
+            }
+        }
+    }
+}
+
+/// Returns the value of every square, with 0 for each empty square.
+fn values(grid: &str) -> [usize; 81] {
+    let mut values = [0; 81];
+    for (value, c) in values.iter_mut().zip(grid.chars()) {
+        *value = c.to_digit(10).unwrap_or(0) as usize;
+    }
+    values
+}
+
+/// Returns every rule the solution breaks: changed givens first, then empty squares,
+/// then values repeated in a row, column, or box. A correct solution has no violations.
+pub fn verify(puzzle: &str, solution: &str) -> Vec<Violation> {
+    let (givens, values) = (self::values(puzzle), self::values(solution));
+    let mut violations = Vec::new();
+    for (index, (&given, &value)) in givens.iter().zip(values.iter()).enumerate() {
+        if 0 != given && given != value {
+            violations.push(Violation::ChangedGiven {
+                square: (index / 9, index % 9),
+                given,
+                value,
+            });
+        }
+    }
+    for (index, &value) in values.iter().enumerate() {
+        if 0 == value && 0 == givens[index] {
+            violations.push(Violation::Empty {
+                square: (index / 9, index % 9),
+            });
+        }
+    }
+    // masks[i] marks the values present in row i, column i, and box i, the same way as a board does.
+    let mut masks = [SudokuData::default(); 9];
+    let mut repeated = Vec::new();
+    for (index, &value) in values.iter().enumerate() {
+        if 0 == value {
+            continue;
+        }
+        let (row, col) = (index / 9, index % 9);
+        let bx = 3 * (row / 3) + col / 3;
+        if 0 != masks[row].values_in_row() & as_bit(value) {
+            repeated.push((House::Row(row), value));
+        }
+        if 0 != masks[col].values_in_col() & as_bit(value) {
+            repeated.push((House::Col(col), value));
+        }
+        if 0 != masks[bx].values_in_box() & as_bit(value) {
+            repeated.push((House::Box(bx), value));
+        }
+        masks[row].mark_in_row(value);
+        masks[col].mark_in_col(value);
+        masks[bx].mark_in_box(value);
+    }
+    let rank = |house: &House| match *house {
+        House::Row(index) => index,
+        House::Col(index) => 9 + index,
+        House::Box(index) => 18 + index,
+    };
+    repeated.sort_by_key(|(house, value)| (rank(house), *value));
+    repeated.dedup();
+    for (house, value) in repeated {
+        let squares = (0..81)
+            .filter(|&index| value == values[index] && house.contains(index / 9, index % 9))
+            .map(|index| (index / 9, index % 9))
+            .collect();
+        violations.push(Violation::Repeated {
+            house,
+            value,
+            squares,
+        });
+    }
+    violations
+}
+
+/// Returns which of the 81 squares are involved in any of the violations.
+pub fn highlighted(violations: &[Violation]) -> [bool; 81] {
+    let mut highlighted = [false; 81];
+    for (row, col) in violations.iter().flat_map(Violation::squares) {
+        highlighted[9 * row + col] = true;
+    }
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::board::SudokuBoard;
+
+    const PUZZLE: &str =
+        ".75.....42139.5.7...8.7...9..2417...4...6...1...8324..3...9.7...5.3.46988.....31.";
+
+    #[test]
+    fn reports_every_violation() {
+        let solution = SudokuBoard::from(PUZZLE).find_up_to(1).remove(0);
+        assert_eq!(Vec::<Violation>::new(), verify(PUZZLE, &solution));
+
+        // Swap the first two values of the last row, which changes a given, and empty the last square.
+        let mut broken: Vec<char> = solution.chars().collect();
+        broken.swap(72, 73);
+        broken[80] = '.';
+        let broken: String = broken.into_iter().collect();
+        let violations = verify(PUZZLE, &broken);
+        let (first, second) = (
+            solution[72..73].parse().unwrap(),
+            solution[73..74].parse().unwrap(),
+        );
+        assert_eq!(
+            Violation::ChangedGiven {
+                square: (8, 0),
+                given: first,
+                value: second,
+            },
+            violations[0]
+        );
+        assert_eq!(Violation::Empty { square: (8, 8) }, violations[1]);
+        assert_eq!(4, violations.len());
+        for (violation, (col, value)) in violations[2..].iter().zip(&[(0, second), (1, first)]) {
+            match violation {
+                Violation::Repeated {
+                    house: House::Col(index),
+                    value: repeated,
+                    squares,
+                } => {
+                    assert_eq!((col, value), (index, repeated));
+                    assert_eq!(2, squares.len());
+                    assert!(squares.contains(&(8, *col)));
+                }
+                violation => panic!("unexpected violation: {}", violation),
+            }
+        }
+        let highlighted = highlighted(&violations);
+        assert!(highlighted[72] && highlighted[80] && !highlighted[40]);
+    }
+
+    #[test]
+    fn repeated_values() {
+        let solution = "1".repeat(81);
+        let violations = verify(&".".repeat(81), &solution);
+        assert_eq!(27, violations.len());
+        assert_eq!(
+            "row 0 has 1 at r0c0, r0c1, r0c2, r0c3, r0c4, r0c5, r0c6, r0c7, r0c8",
+            violations[0].to_string()
+        );
+        assert_eq!(
+            "box 8 has 1 at r6c6, r6c7, r6c8, r7c6, r7c7, r7c8, r8c6, r8c7, r8c8",
+            violations[26].to_string()
+        );
+        assert_eq!(
+            "r0c1 is 2 but the given is 7",
+            verify(PUZZLE, &PUZZLE.replace("75", "25"))[0].to_string()
+        );
+    }
+}